@@ -2,27 +2,54 @@ pub mod input;
 pub mod sender;
 pub mod errors;
 pub mod utils;
-
-mod effects;
-mod filters;
-mod processing;
+pub mod processing;
+pub mod effects;
+pub mod filters;
+pub mod events;
+pub mod output;
 
 
 use input::*;
-use utils::AudioBuffer;
+use input::store::DeviceStore;
+use input::generator::{GeneratorInputSource, Signal};
+use events::{EngineEvent, EventBus};
+use output::{OutputConfig, OutputPipeline, OutputSink};
+use output::layout::LedLayout;
+use output::palette::ColorScheme;
+use output::pixel::{Color, PixelFrame};
+use processing::Spectrum;
+use processing::window::Window;
+use processing::mel::{MelCache, MelFilterbank, DEFAULT_MAX_FREQUENCY, DEFAULT_MIN_FREQUENCY};
 
 use effects::{EffectProcessing, Effect, EffectInfo};
 use effects::frequency::FrequencyEffect;
-use filters::{FilterProcessing, Filter, FilterInfo};
+use effects::matrix::{RadialSpectrumEffect, SpectrogramEffect, SpectrumBarsEffect};
+use effects::wave::{EnvelopeEffect, OscilloscopeEffect, WaveformScrollEffect};
+use filters::{FilterProcessing, Filter, FilterInfo, SimplePreEmphasisFilter};
 
-use cpal::{InputCallbackInfo};
-use crate::engine::utils::Domain;
+use crate::engine::utils::{BufferInfo, Domain, StereoLayout};
 use errors::ApplicationError;
 use anyhow::Result;
-
+use log::warn;
+use cpal::StreamError;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+/// Shared slot of the output sink, which is used by the engine and the worker.
+/// A sink, which panicked inside the audio thread, poisons the mutex. The slot is still used afterwards,
+/// so the engine can stop and drop the sink without panicking itself.
+type SharedSink = Arc<Mutex<Option<Box<dyn OutputSink>>>>;
+
+/// Processes the audio of the input source into the frames of the sink.
+///
+/// The engine doesn't run a thread of its own. Errors of the stream, e.g. an unplugged device,
+/// are only handled inside *maintain_stream*. The application has to call it regularly,
+/// e.g. once per iteration of its main loop. Otherwise a failed device is neither replaced nor recovered.
 pub struct Engine {
-    input: DeviceInputSource,
+    input: Box<dyn InputSource>,
     // send handler
+    sink: SharedSink,
 
     //Led amount of the device
     n_led: usize,
@@ -34,15 +61,62 @@ pub struct Engine {
     filtering: bool,
     current_effect: usize,
     current_filter: usize,
+    // Coefficient of the pre emphasis filter
+    pre_emphasis: f32,
+
+    // Window function before the fourier transformation
+    window: Window,
+
+    // Frequency range of the mel filterbank
+    min_frequency: f32,
+    max_frequency: f32,
+    mel_cache: MelCache,
+
+    // Framing of the input data
+    input_config: InputConfig,
+
+    // Distribution of the channels on the leds
+    stereo_layout: StereoLayout,
+
+    // Remembers the selected device
+    device_store: Option<DeviceStore>,
+
+    // Conversion of the frames into the bytes of the sink
+    output_config: OutputConfig,
+    led_layout: Option<LedLayout>,
+
+    // Errors of the stream, which are reported from the audio thread
+    error_sender: Sender<StreamError>,
+    error_receiver: Receiver<StreamError>,
+    events: EventBus,
+
+    // Recovery after a stream error
+    // The name is kept, because an unplugged device can't report its name anymore
+    selected_device: Option<String>,
+    recovery: Option<Recovery>,
+    // Original input while the silent source is used
+    suspended_input: Option<Box<dyn InputSource>>,
+    retry_interval: Duration,
 }
 
+/// The device which failed and should be selected again
+struct Recovery {
+    device: String,
+    last_attempt: Instant
+}
 
-impl Engine {
 
+impl Engine {
+    /// Time between two attempts to select the failed device again
+    const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+    /// Position of the pre emphasis filter in the list of filters
+    const PRE_EMPHASIS_FILTER: usize = 0;
+    /// Sample rate of the silent source, if no internal sample rate is configured
+    const FALLBACK_SAMPLE_RATE: u32 = 48000;
 
-    /// Generates a new engine with all necessary dependencies
-    pub fn new(n_led: usize) -> Engine {
-        let input = DeviceInputSource::new();
+    /// Generates a new engine with all necessary dependencies.
+    /// The input source delivers the audio data, e.g. a *DeviceInputSource* for the pc's audio input.
+    pub fn new(input: Box<dyn InputSource>, n_led: usize) -> Engine {
 
         let effects: Vec<Effect> = vec![
             Effect::new(
@@ -50,21 +124,78 @@ impl Engine {
                 "/fdgfd",
                 Domain::FrequencyDomain,
                 Box::new(FrequencyEffect)
+            ),
+            Effect::new(
+                "Oscilloscope",
+                "/oscilloscope",
+                Domain::TimeDomain,
+                Box::new(OscilloscopeEffect)
+            ),
+            Effect::new(
+                "Waveform Scroll",
+                "/waveform",
+                Domain::TimeDomain,
+                Box::new(WaveformScrollEffect)
+            ),
+            Effect::new(
+                "Envelope",
+                "/envelope",
+                Domain::TimeDomain,
+                Box::new(EnvelopeEffect::new())
+            ),
+            Effect::new_canvas(
+                "Spectrum Bars",
+                "/bars",
+                Domain::FrequencyDomain,
+                Box::new(SpectrumBarsEffect)
+            ),
+            Effect::new_canvas(
+                "Spectrogram",
+                "/spectrogram",
+                Domain::FrequencyDomain,
+                Box::new(SpectrogramEffect)
+            ),
+            Effect::new_canvas(
+                "Radial Spectrum",
+                "/radial",
+                Domain::FrequencyDomain,
+                Box::new(RadialSpectrumEffect)
             )
-
-
-
         ];
-        let filters: Vec<Filter> = vec![];
+        let filters: Vec<Filter> = vec![
+            Self::pre_emphasis_filter(SimplePreEmphasisFilter::DEFAULT_COEFFICIENT)
+        ];
+
+        let (error_sender, error_receiver) = channel();
+        // The initial device of the input is selected as well
+        let selected_device = input.current_device_name().ok();
 
         Engine {
             input,
+            sink: Arc::new(Mutex::new(None)),
             n_led,
             effects,
             filters,
             filtering: false,
             current_effect: 0,
-            current_filter: 0
+            current_filter: 0,
+            pre_emphasis: SimplePreEmphasisFilter::DEFAULT_COEFFICIENT,
+            window: Window::default(),
+            min_frequency: DEFAULT_MIN_FREQUENCY,
+            max_frequency: DEFAULT_MAX_FREQUENCY,
+            mel_cache: MelCache::new(),
+            input_config: InputConfig::default(),
+            stereo_layout: StereoLayout::default(),
+            device_store: None,
+            output_config: OutputConfig::default(),
+            led_layout: None,
+            error_sender,
+            error_receiver,
+            events: EventBus::new(),
+            selected_device,
+            recovery: None,
+            suspended_input: None,
+            retry_interval: Self::DEFAULT_RETRY_INTERVAL
         }
     }
 
@@ -72,17 +203,100 @@ impl Engine {
 
     /// Get all currently available devices which can be used as data input
     pub fn get_available_devices(&self) -> Result<Vec<DeviceInfo>> {
-        self.input.available_devices()
+        match &self.suspended_input {
+            None => self.input.available_devices(),
+            Some(input) => input.available_devices()
+        }
     }
 
     /// Set a specific device as data input
     pub fn set_device(&mut self, position: usize) -> Result<()> {
+        self.stop_recovery();
         self.input.set_device(position)?;
+        self.select_device()?;
 
         // Update the stream after the device was changed
         self.update_stream()
     }
 
+    /// Set the device with the best matching name as data input.
+    /// The name doesn't have to be complete, e.g. "focusrite" selects "Focusrite USB Audio".
+    pub fn set_device_by_name(&mut self, name: &str) -> Result<()> {
+        self.stop_recovery();
+        self.input.set_device_by_name(name)?;
+        self.select_device()?;
+
+        // Update the stream after the device was changed
+        self.update_stream()
+    }
+
+    /// Get the name of the current device
+    pub fn get_current_device_name(&self) -> Result<String> {
+        self.input.current_device_name()
+    }
+
+    /// Remember every selected device in the store.
+    /// If the store already contains a device, which is available, it will be selected.
+    /// Otherwise the current device stays selected.
+    pub fn set_device_store(&mut self, store: DeviceStore) -> Result<()> {
+        let name = store.load()?;
+        self.device_store = Some(store);
+
+        let name = match name {
+            None => return Ok(()),
+            Some(value) => value
+        };
+
+        match self.input.set_device_by_name(&name) {
+            Ok(_) => {
+                self.selected_device = self.input.current_device_name().ok();
+                self.update_stream()
+            }
+            Err(err) => {
+                warn!("The stored device {} can't be selected: {}", name, err);
+                Ok(())
+            }
+        }
+    }
+
+    /// Receive all events of the engine, e.g. stream errors and the recovery of devices
+    pub fn subscribe(&mut self) -> Receiver<EngineEvent> {
+        self.events.subscribe()
+    }
+
+    /// Set the time between two attempts to select a failed device again
+    pub fn set_retry_interval(&mut self, interval: Duration) {
+        self.retry_interval = interval
+    }
+
+    /// Check if the engine currently uses a fallback instead of the selected device
+    pub fn is_recovering(&self) -> bool {
+        self.recovery.is_some() || self.suspended_input.is_some()
+    }
+
+    /// Handle the errors of the stream and retry the failed device.
+    /// Has to be called regularly, e.g. in the main loop of the application.
+    /// Nothing is recovered automatically, so without these calls the output stops after a stream error.
+    ///
+    /// If the stream reports an error, the default device is used instead.
+    /// If the default device isn't available or failed as well, a silent source is used.
+    /// After every retry interval the failed device is selected again, as soon as it is available.
+    pub fn maintain_stream(&mut self) -> Result<()> {
+        let errors: Vec<StreamError> = self.error_receiver.try_iter().collect();
+
+        for error in errors.iter() {
+            warn!("Stream error: {}", error);
+            self.events.emit(EngineEvent::StreamError(error.to_string()));
+        }
+
+        // The silent source doesn't fail, so only the device streams have to be replaced
+        if !errors.is_empty() && self.suspended_input.is_none() {
+            return self.fall_back()
+        }
+
+        self.retry_device()
+    }
+
     /// Start function
     /// Drops the current stream and start a new stream
     pub fn update_stream(&mut self) -> Result<()> {
@@ -94,7 +308,56 @@ impl Engine {
 
     /// Stops the current stream.
     pub fn pause_stream(&self) -> Result<()> {
-        self.input.pause_stream()
+        self.input.pause_stream()?;
+
+        self.stop_sink()
+    }
+
+    /// Set the output, which receives every processed frame.
+    /// The old sink will be stopped, the new sink is started with the next stream.
+    pub fn set_sink(&mut self, sink: Box<dyn OutputSink>) -> Result<()> {
+        self.stop_sink()?;
+        *self.sink.lock().unwrap_or_else(PoisonError::into_inner) = Some(sink);
+
+        Ok(())
+    }
+
+    /// Get the configuration of the output path
+    pub fn get_output_config(&self) -> OutputConfig {
+        self.output_config
+    }
+
+    /// Set the configuration of the output path, e.g. the pixel format of the leds
+    pub fn set_output_config(&mut self, config: OutputConfig) -> Result<()> {
+        config.validate()?;
+        self.output_config = config;
+
+        self.update_stream()
+    }
+
+    /// Get the layout of the physical leds
+    pub fn get_led_layout(&self) -> Option<&LedLayout> {
+        self.led_layout.as_ref()
+    }
+
+    /// Set the layout of the physical leds.
+    /// The effects render the canvas of the layout instead of *n_led* leds.
+    /// Without a layout the output is sent in the order of the effect.
+    /// A layout only covers the canvas of one channel, so it can't be combined with *StereoLayout::Separate*.
+    pub fn set_led_layout(&mut self, layout: Option<LedLayout>) -> Result<()> {
+        if layout.is_some() && self.stereo_layout == StereoLayout::Separate {
+            Err(ApplicationError::InvalidLayout(String::from("A led layout can't be used with separate stereo channels")))?
+        }
+        self.led_layout = layout;
+
+        self.update_stream()
+    }
+
+    /// Stop and remove the current output
+    pub fn remove_sink(&mut self) -> Result<Option<Box<dyn OutputSink>>> {
+        self.stop_sink()?;
+
+        Ok(self.sink.lock().unwrap_or_else(PoisonError::into_inner).take())
     }
 
     /// Get all available effects
@@ -126,6 +389,27 @@ impl Engine {
         self.update_stream()
     }
 
+    /// Get the colour scheme of the effect
+    pub fn get_color_scheme(&self, position: usize) -> Result<ColorScheme> {
+        let effect = self.effects.get(position)
+            .ok_or(ApplicationError::EffectNotFound { id: position })?;
+
+        Ok(effect.color_scheme().clone())
+    }
+
+    /// Set the palette and the colour mapping of the effect.
+    /// Every effect keeps its own scheme, so it's restored when the effect is selected again.
+    pub fn set_color_scheme(&mut self, position: usize, scheme: ColorScheme) -> Result<()> {
+        let effect = self.effects.get_mut(position)
+            .ok_or(ApplicationError::EffectNotFound { id: position })?;
+        effect.set_color_scheme(scheme);
+
+        match position == self.current_effect {
+            true => self.update_stream(),
+            false => Ok(())
+        }
+    }
+
     // Set the current filter
     pub fn set_filter(&mut self, position: usize) -> Result<()> {
         self.current_filter = position;
@@ -141,66 +425,329 @@ impl Engine {
         self.filtering = value
     }
 
+    /// Get the coefficient of the pre emphasis filter
+    pub fn get_pre_emphasis(&self) -> f32 {
+        self.pre_emphasis
+    }
+
+    /// Set the coefficient of the pre emphasis filter between 0 and 1.
+    /// A higher coefficient lowers the amplitude of the low frequencies more.
+    pub fn set_pre_emphasis(&mut self, coefficient: f32) -> Result<()> {
+        if !(0.0..=1.0).contains(&coefficient) {
+            Err(ApplicationError::InvalidPreEmphasis(coefficient))?
+        }
+
+        self.pre_emphasis = coefficient;
+        self.filters[Self::PRE_EMPHASIS_FILTER] = Self::pre_emphasis_filter(coefficient);
+
+        self.update_stream()
+    }
+
+    /// Get the window function, which is applied before the fourier transformation
+    pub fn get_window(&self) -> Window {
+        self.window
+    }
+
+    /// Set the window function, which is applied before the fourier transformation
+    pub fn set_window(&mut self, window: Window) -> Result<()> {
+        self.window = window;
+
+        self.update_stream()
+    }
+
+    /// Get the frequency range in Hz, which is covered by the mel bins
+    pub fn get_frequency_range(&self) -> (f32, f32) {
+        (self.min_frequency, self.max_frequency)
+    }
+
+    /// Set the frequency range in Hz, which is covered by the mel bins.
+    /// If the stream can't be built with the new range, the previous range is restored.
+    pub fn set_frequency_range(&mut self, min: f32, max: f32) -> Result<()> {
+        if !min.is_finite() || !max.is_finite() || min < 0.0 || min >= max {
+            Err(ApplicationError::InvalidFrequencyRange { min, max })?
+        }
+
+        let previous = (self.min_frequency, self.max_frequency);
+        self.min_frequency = min;
+        self.max_frequency = max;
+
+        if let Err(err) = self.update_stream() {
+            (self.min_frequency, self.max_frequency) = previous;
+            self.update_stream()?;
+            Err(err)?
+        }
+
+        Ok(())
+    }
+
+    /// Get the configuration of the input path
+    pub fn get_input_config(&self) -> InputConfig {
+        self.input_config
+    }
+
+    /// Set the configuration of the input path, e.g. the window and hop length
+    pub fn set_input_config(&mut self, config: InputConfig) -> Result<()> {
+        config.validate()?;
+        self.input_config = config;
+
+        self.update_stream()
+    }
+
+
+    /// Get the capture and the display frame rate
+    pub fn get_frame_rates(&self) -> (u32, u32) {
+        (self.input_config.capture_frame_rate, self.input_config.display_frame_rate)
+    }
+
+    /// Set the capture and the display frame rate.
+    /// The capture frame rate must be a multiple of the display frame rate.
+    pub fn set_frame_rates(&mut self, capture: u32, display: u32) -> Result<()> {
+        self.set_input_config(InputConfig {
+            capture_frame_rate: capture,
+            display_frame_rate: display,
+            ..self.input_config
+        })
+    }
+
+
+    /// Get the distribution of the channels on the leds
+    pub fn get_stereo_layout(&self) -> StereoLayout {
+        self.stereo_layout
+    }
+
+    /// Set the distribution of the channels on the leds.
+    /// Only takes effect, if the input config keeps stereo channels.
+    /// *StereoLayout::Separate* can't be combined with a led layout.
+    pub fn set_stereo_layout(&mut self, layout: StereoLayout) -> Result<()> {
+        if layout == StereoLayout::Separate && self.led_layout.is_some() {
+            Err(ApplicationError::InvalidLayout(String::from("Separate stereo channels can't be used with a led layout")))?
+        }
+        self.stereo_layout = layout;
+
+        self.update_stream()
+    }
+
+    /// Amount of led values, which are created for every frame
+    pub fn get_output_length(&self) -> usize {
+        self.stereo_layout.output_length(self.led_count(), self.input_config.channels.output_channels())
+    }
+
+    /// Amount of pixels, which are sent to the sink for every frame
+    pub fn get_pixel_count(&self) -> usize {
+        match &self.led_layout {
+            Some(layout) => layout.physical_length(),
+            None => self.get_output_length()
+        }
+    }
+
 
     //---------------------Private-Methods---------------------------------
 
+    /// Replace the failed stream with the default device or the silent source
+    fn fall_back(&mut self) -> Result<()> {
+        let failed = self.selected_device.clone();
+
+        // Keep the originally selected device, if the default device failed as well
+        if self.recovery.is_none() {
+            self.recovery = failed.clone().map(|device| Recovery {
+                device,
+                last_attempt: Instant::now()
+            });
+        }
+
+        let default = self.input.set_default_device()
+            .and_then(|_| self.input.current_device_name());
+        if let Ok(name) = default {
+            if Some(&name) != failed.as_ref() && self.update_stream().is_ok() {
+                self.events.emit(EngineEvent::FallbackDevice(name));
+                return Ok(())
+            }
+        }
+
+        let sample_rate = self.input_config.sample_rate.unwrap_or(Self::FALLBACK_SAMPLE_RATE);
+        let silence = GeneratorInputSource::new(Signal::Silence, sample_rate, Playback::RealTime);
+        self.suspended_input = Some(std::mem::replace(&mut self.input, Box::new(silence)));
+        self.events.emit(EngineEvent::FallbackSilence);
+
+        self.update_stream()
+    }
+
+    /// Select the failed device again, if it is available
+    fn retry_device(&mut self) -> Result<()> {
+        let recovery = match &mut self.recovery {
+            Some(value) if value.last_attempt.elapsed() >= self.retry_interval => value,
+            _ => return Ok(())
+        };
+        recovery.last_attempt = Instant::now();
+        let name = recovery.device.clone();
+
+        let input = self.suspended_input.as_mut().unwrap_or(&mut self.input);
+        let available = input.available_devices()
+            .map(|devices| devices.iter().any(|device| device.name == name))
+            .unwrap_or(false);
+        if !available { return Ok(()) }
+
+        input.set_device_by_name(&name)?;
+        self.stop_recovery();
+        self.update_stream()?;
+
+        self.events.emit(EngineEvent::DeviceRecovered(name));
+        Ok(())
+    }
+
+    /// Use the original input again and stop the retries
+    fn stop_recovery(&mut self) {
+        if let Some(input) = self.suspended_input.take() {
+            self.input = input;
+        }
+        self.recovery = None;
+    }
+
+    /// Keep the name of the current device for the recovery and write it to the store
+    fn select_device(&mut self) -> Result<()> {
+        let name = self.input.current_device_name()?;
+        if let Some(store) = &self.device_store {
+            store.save(&name)?;
+        }
+        self.selected_device = Some(name);
+
+        Ok(())
+    }
+
     /// Start the current stream
     fn start_stream(&self) -> Result<()> {
         self.input.start_stream()
     }
 
+    /// Start the sink with the frame rate of the current stream
+    fn start_sink(&self, info: &BufferInfo) -> Result<()> {
+        match self.sink.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+            None => Ok(()),
+            Some(sink) => sink.start(info.frame_rate().ceil() as u32)
+        }
+    }
+
+    /// Stop the current sink
+    fn stop_sink(&self) -> Result<()> {
+        match self.sink.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+            None => Ok(()),
+            Some(sink) => sink.stop()
+        }
+    }
+
+
+    /// Amount of leds, which are rendered by the effects
+    fn led_count(&self) -> usize {
+        match &self.led_layout {
+            Some(layout) => layout.canvas_length(),
+            None => self.n_led
+        }
+    }
+
+    /// Width of the canvas, a strip without layout is a single row
+    fn canvas_width(&self) -> usize {
+        match &self.led_layout {
+            Some(layout) => layout.width(),
+            None => self.n_led
+        }
+    }
+
+    /// Width of the canvas of every channel and their common height.
+    /// With *StereoLayout::Split* the channels share the columns of the canvas, so every channel keeps the full height.
+    fn channel_canvases(&self, channels: usize) -> (Vec<usize>, usize) {
+        let width = self.canvas_width();
+        let widths = (0..channels)
+            .map(|channel| self.stereo_layout.channel_width(width, channels, channel))
+            .collect();
+
+        (widths, self.led_count() / width.max(1))
+    }
 
     fn get_current_effect(&self) -> Result<&Effect> {
-        self.effects.get(self.current_effect)
-            .ok_or(
-                Err(ApplicationError::EffectNotFound {
-                    id: self.current_effect
-                })?
-            )
+        let effect = self.effects.get(self.current_effect)
+            .ok_or(ApplicationError::EffectNotFound {
+                id: self.current_effect
+            })?;
+
+        Ok(effect)
+    }
+
+    fn pre_emphasis_filter(coefficient: f32) -> Filter {
+        Filter::new(
+            "Pre-Emphasis",
+            Domain::TimeDomain,
+            Box::new(SimplePreEmphasisFilter::new(coefficient))
+        )
     }
 
     fn get_current_filter(&self) -> Option<&Filter> {
-        if self.filtering { None? }
+        if !self.filtering { None? }
 
         self.filters.get(self.current_filter)
     }
 
-    fn get_frame_length(&self) -> Result<usize> {
-        let info = self.input.buffer_info()?;
+    /// Get the filterbank for the current effect and the current sample rate.
+    /// The mel points of the widest channel are enough for every channel.
+    fn get_filterbank(&mut self, info: &BufferInfo) -> Result<Arc<MelFilterbank>> {
+        let (widths, height) = self.channel_canvases(info.channels);
+        let width = widths.iter().copied().max().unwrap_or_default();
+        let n_mel = self.get_current_effect()?.create(width).n_mel(width * height);
 
-        Ok(info.frame_length)
+        self.mel_cache.get(info.sample_rate, n_mel, self.min_frequency, self.max_frequency)
     }
 
 
     fn build_stream(&mut self) -> Result<()> {
 
-        let frame_length = self.get_frame_length()?;
-        let effect = self.get_current_effect()?.create();
+        let info = self.input.buffer_info(&self.input_config)?;
+        let domain = self.get_current_effect()?.domain();
+        let (widths, _) = self.channel_canvases(info.channels);
+        let effects = widths.iter()
+            .map(|width| Ok(self.get_current_effect()?.create(*width)))
+            .collect::<Result<Vec<_>>>()?;
+        let scheme = self.get_current_effect()?.color_scheme().clone();
+        let filterbank = self.get_filterbank(&info)?;
         let filter = match self.get_current_filter() {
             Some(value) => Some(value.create()),
             None => None
         };
 
+        let error_sender = self.error_sender.clone();
+
+        self.start_sink(&info)?;
+
         // Define callback
-        let call =  |data: &[i16]| {
-            println!("First: {:?}", data[0]);
+        let sink = self.sink.clone();
+        let mut pipeline = OutputPipeline::new(self.output_config, self.led_layout.clone());
+        let mut bytes = vec![];
+        let call = move |frame: &PixelFrame| {
+            if let Some(sink) = sink.lock().unwrap_or_else(PoisonError::into_inner).as_mut() {
+                pipeline.process(frame, &mut bytes);
+
+                // The audio thread can't return the error, so it can only be logged
+                if let Err(err) = sink.write(&bytes) {
+                    warn!("The frame can't be sent: {}", err);
+                }
+            }
         };
 
         {
             //Build the worker & stream
             let mut worker = Worker::new(
-                call, frame_length,
-                self.n_led, effect, filter);
+                call, self.window, info, self.led_count(), self.canvas_width(), self.stereo_layout,
+                filterbank, domain, effects, scheme, filter);
 
-            self.input.build_mono_stream(
-                move |data, info| {
+            self.input.build_stream(
+                &self.input_config,
+                Box::new(move |data| {
 
-                    worker.process(data, info)
+                    worker.process(data)
 
-                },
-                move |err| {
-                    todo!()
-                }
+                }),
+                Box::new(move |err| {
+                    // The engine handles the error in *maintain_stream*. If the engine was dropped, nobody is interested
+                    let _ = error_sender.send(err);
+                })
 
             )?;
         }
@@ -212,6 +759,14 @@ impl Engine {
 }
 
 
+impl Drop for Engine {
+    /// Turn the leds off, when the engine isn't used anymore
+    fn drop(&mut self) {
+        let _ = self.pause_stream();
+    }
+}
+
+
 //TODO: 1. Framing, 2. Effect, 3. Domain, 4. Filter, N_FFT, N_Melbank, N_LEDs
 //TODO:  Hat: Raw audio buffer
 
@@ -224,83 +779,171 @@ pub struct Worker<C>
     // A Mutable function which gets the effect data is needed.
     // Because of that this Worker struct runs in another thread the callback must have
     // implemented the Send Trait.
-        C: FnMut(&[i16]) + Send + 'static,
+        C: FnMut(&PixelFrame) + Send + 'static,
 {
     //Input and Output
     callback: C,
-    last_frame: AudioBuffer<i16>,
-    fft_buffer: [i16; processing::N_FFT],
-    //mel buffer -> heap
-    //effect buffer -> heap
-
-    n_led: usize,
+    info: BufferInfo,
+    power_buffer: [f32; processing::N_BINS],
+    filterbank: Arc<MelFilterbank>,
+    channel_pixels: Vec<Color>,
+    frame: PixelFrame,
+    scheme: ColorScheme,
 
     //Framing factor
-    effect: Box<dyn EffectProcessing + Send>,
-    filter: Option<Box<dyn FilterProcessing + Send>>
+    domain: Domain,
+    // The canvases of the channels are placed side by side in every row of the frame
+    row_width: usize,
+    channels: Vec<ChannelWorker>
 
 }
 
 
 impl<C> Worker<C>
 where
-    C: FnMut(&[i16]) + Send + 'static
+    C: FnMut(&PixelFrame) + Send + 'static
 {
 
-    /// Generates a new Worker struct
+    /// Generates a new Worker struct.
+    /// Every channel of the stream gets its own instance of the effect and the filter.
+    /// The canvas of n_led leds with the given width is split by its columns between the channels,
+    /// the effects have to be created for the width of their channel.
+    #[allow(clippy::too_many_arguments)]
     fn new(
         callback: C,
-        frame_length: usize,
+        window: Window,
+        info: BufferInfo,
         n_led: usize,
-        effect: Box<dyn EffectProcessing + Send>,
+        width: usize,
+        layout: StereoLayout,
+        filterbank: Arc<MelFilterbank>,
+        domain: Domain,
+        effects: Vec<Box<dyn EffectProcessing + Send>>,
+        scheme: ColorScheme,
         filter: Option<Box<dyn FilterProcessing + Send>>
     ) -> Self {
-        let last_frame = AudioBuffer::new(frame_length, 1);
-        let fft_buffer = [0; processing::N_FFT];
-
-
+        let n_mel = filterbank.n_mel();
+        let output_length = layout.output_length(n_led, info.channels);
+        let width = width.max(1);
+        let height = n_led / width;
+
+        let mut column = 0;
+        let channels: Vec<ChannelWorker> = effects.into_iter()
+            .enumerate()
+            .map(|(channel, effect)| {
+                let channel_width = layout.channel_width(width, info.channels, channel);
+                column += channel_width;
+
+                ChannelWorker {
+                    last_frame: vec![],
+                    spectrum: Spectrum::new(window, info.window_length),
+                    mel_power: vec![0.0; n_mel],
+                    mel_buffer: vec![0; n_mel],
+                    output: vec![0; channel_width * height],
+                    column: column - channel_width,
+                    width: channel_width,
+                    effect,
+                    filter: filter.clone()
+                }
+            })
+            .collect();
+        let max_leds = channels.iter().map(|channel| channel.output.len()).max().unwrap_or_default();
 
         Worker {
-            callback, last_frame, fft_buffer,
-            n_led, effect, filter
+            callback,
+            info,
+            power_buffer: [0.0; processing::N_BINS],
+            filterbank,
+            channel_pixels: vec![Color::BLACK; max_leds],
+            frame: PixelFrame::new(output_length),
+            scheme,
+            domain,
+            row_width: layout.output_length(width, info.channels),
+            channels
         }
     }
 
-    /// Function which consumes the raw input data and process the effect
-    fn process(&mut self, data: &[i16], info: &InputCallbackInfo) {
-        //....
-
-        (self.callback)(data)
-    }
-
-
-    /// Process the pre emphasis over the input signal
-    fn pre_emphasis(&mut self) {
-
-    }
-
-}
-
-
-
-
-
-
+    /// Function which consumes the raw input data and process the effect.
+    /// The canvas of every channel is placed in every row behind the canvas of the previous channel,
+    /// e.g. the left channel is shown on the left half of a matrix.
+    fn process(&mut self, data: &[i16]) {
+        let windows = data.chunks_exact(self.info.window_length.max(1));
 
+        for (channel, window) in self.channels.iter_mut().zip(windows) {
+            channel.update_frame(window, self.info.hop_length);
+            let output = channel.output.as_mut_slice();
 
+            match self.domain {
+                Domain::FrequencyDomain => {
+                    channel.spectrum.power(&channel.last_frame, &mut self.power_buffer);
+                    self.filterbank.apply(&self.power_buffer, &mut channel.mel_power);
+                    processing::quantize(&channel.mel_power, channel.spectrum.max_power(), &mut channel.mel_buffer);
 
+                    channel.effect.process_frequency(&channel.mel_buffer, output);
+                }
+                Domain::TimeDomain => {
+                    channel.effect.process_wave(&channel.last_frame, output);
+                }
+            }
 
+            let pixels = &mut self.channel_pixels[..output.len()];
+            channel.effect.colorize(output, &self.scheme, pixels);
 
+            let rows = self.frame.pixels_mut().chunks_exact_mut(self.row_width.max(1));
+            for (row, pixels) in rows.zip(pixels.chunks_exact(channel.width.max(1))) {
+                row[channel.column..channel.column + pixels.len()].copy_from_slice(pixels);
+            }
+        }
 
+        (self.callback)(&self.frame)
+    }
 
+}
 
 
+/// State of the worker for a single channel
+struct ChannelWorker {
+    last_frame: Vec<i16>,
+    spectrum: Spectrum,
+    mel_power: Vec<f32>,
+    mel_buffer: Vec<i16>,
 
+    // Output of the effect, which keeps the last frame
+    output: Vec<i16>,
+    // First column and amount of columns of the channel inside a row of the frame
+    column: usize,
+    width: usize,
 
+    effect: Box<dyn EffectProcessing + Send>,
+    filter: Option<Box<dyn FilterProcessing + Send>>
+}
 
+impl ChannelWorker {
 
+    /// Copy the window, so the filter can change it.
+    /// Overlapping windows share their samples, so only the new hop will be filtered.
+    /// Otherwise a stateful filter would see the same samples multiple times.
+    fn update_frame(&mut self, data: &[i16], hop_length: usize) {
+        let hop = hop_length.min(data.len());
 
+        if self.filter.is_none() || self.last_frame.len() != data.len() || hop == data.len() {
+            self.last_frame.clear();
+            self.last_frame.extend_from_slice(data);
 
+            if let Some(filter) = &mut self.filter {
+                filter.process(&mut self.last_frame);
+            }
+            return
+        }
 
+        // Keep the already filtered samples and append the new hop
+        let start = data.len() - hop;
+        self.last_frame.copy_within(hop.., 0);
+        self.last_frame[start..].copy_from_slice(&data[start..]);
 
+        if let Some(filter) = &mut self.filter {
+            filter.process(&mut self.last_frame[start..]);
+        }
+    }
 
+}
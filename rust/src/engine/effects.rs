@@ -1,7 +1,14 @@
 use dyn_clone::DynClone;
 use super::utils::Domain;
+use super::output::palette::ColorScheme;
+use super::output::pixel::Color;
 
+pub mod canvas;
 pub mod frequency;
+pub mod matrix;
+pub mod wave;
+
+use canvas::{CanvasEffect, CanvasProcessing};
 
 // Apply the clone trait for every Processing object
 dyn_clone::clone_trait_object!(EffectProcessing);
@@ -11,21 +18,39 @@ dyn_clone::clone_trait_object!(EffectProcessing);
 pub trait EffectProcessing: DynClone {
 
     /// Defines how much mel points should be calculated for this effect.
+    /// Effects of the time domain don't need any mel points.
     fn n_mel(&self, n_led: usize) -> usize;
 
-    /// Processes the effect.
-    fn process_frequency(&self, mel: &[i16], output: &mut [i16]);
+    /// Processes the effect for the mel points of the current frame.
+    /// Only called for effects of the frequency domain.
+    fn process_frequency(&mut self, _mel: &[i16], _output: &mut [i16]) {}
+
+    /// Processes the effect for the raw samples of the current frame.
+    /// Only called for effects of the time domain.
+    fn process_wave(&mut self, _wave: &[i16], _output: &mut [i16]) {}
 
-    //fn process_wave(...)
+    /// Converts the output of the effect into the colours of the leds with the selected colour scheme.
+    /// By default the position of a value is its index inside the output.
+    fn colorize(&mut self, values: &[i16], scheme: &ColorScheme, pixels: &mut [Color]) {
+        scheme.apply(values, pixels)
+    }
 }
 
 // to send the processing trait to the worker (which is another thread),
 // we need to implement the Send trait
 type EffectProcessor = dyn EffectProcessing + Send;
+type CanvasProcessor = dyn CanvasProcessing + Send;
+
+/// Processing of a strip or of a two dimensional canvas
+enum Processor {
+    Strip(Box<EffectProcessor>),
+    Canvas(Box<CanvasProcessor>)
+}
 
 pub struct Effect {
     info: EffectInfo,
-    processor: Box<EffectProcessor>
+    processor: Processor,
+    scheme: ColorScheme
 }
 
 #[derive(Copy, Clone)]
@@ -33,6 +58,8 @@ pub struct EffectInfo {
     pub name: &'static str,
     pub icon: &'static str,
     pub domain: Domain,
+    /// The effect is made for led matrices
+    pub canvas: bool
 }
 
 impl Effect {
@@ -42,9 +69,25 @@ impl Effect {
             info: EffectInfo {
                 name,
                 icon,
-                domain
+                domain,
+                canvas: false
             }
-            , processor 
+            , processor: Processor::Strip(processor),
+            scheme: ColorScheme::default()
+        }
+    }
+
+    /// Create an effect, which renders onto the two dimensional canvas of the led layout
+    pub fn new_canvas(name: &'static str, icon: &'static str, domain: Domain, processor: Box<CanvasProcessor>) -> Effect {
+        Effect {
+            info: EffectInfo {
+                name,
+                icon,
+                domain,
+                canvas: true
+            },
+            processor: Processor::Canvas(processor),
+            scheme: ColorScheme::default()
         }
     }
 
@@ -63,9 +106,22 @@ impl Effect {
         self.info.domain
     }
 
-    /// Create a new boxed Processing trait
-    pub fn create(&self) -> Box<EffectProcessor> {
-        self.processor.clone()
+    /// Create a new boxed Processing trait.
+    /// Canvas effects get the width of the canvas of one channel, their height results from the amount of leds.
+    pub fn create(&self, width: usize) -> Box<EffectProcessor> {
+        match &self.processor {
+            Processor::Strip(processor) => processor.clone(),
+            Processor::Canvas(processor) => Box::new(CanvasEffect::new(processor.clone(), width))
+        }
+    }
+
+    /// Palette and colour mapping of the effect
+    pub fn color_scheme(&self) -> &ColorScheme {
+        &self.scheme
+    }
+
+    pub fn set_color_scheme(&mut self, scheme: ColorScheme) {
+        self.scheme = scheme
     }
 
     // Copy the info from the effect
@@ -0,0 +1,121 @@
+use dyn_clone::DynClone;
+
+use crate::engine::effects::EffectProcessing;
+use crate::engine::output::palette::ColorScheme;
+use crate::engine::output::pixel::Color;
+
+// Apply the clone trait for every canvas processing object
+dyn_clone::clone_trait_object!(CanvasProcessing);
+
+
+/// Two dimensional view on the output of an effect.
+/// The pixels are stored row by row, beginning with the top row.
+pub struct Canvas<'a> {
+    width: usize,
+    height: usize,
+    pixels: &'a mut [i16]
+}
+
+impl<'a> Canvas<'a> {
+
+    /// Create a canvas with the given width. The height results from the length of the pixels,
+    /// incomplete rows at the end are ignored.
+    pub fn new(width: usize, pixels: &'a mut [i16]) -> Canvas<'a> {
+        let width = width.max(1);
+        let height = pixels.len() / width;
+
+        Canvas { width, height, pixels: &mut pixels[..width * height] }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> i16 {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: i16) {
+        self.pixels[y * self.width + x] = value
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [i16] {
+        &mut self.pixels[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [i16] {
+        self.pixels
+    }
+
+    pub fn fill(&mut self, value: i16) {
+        self.pixels.fill(value)
+    }
+
+    /// Move every row one row down. The bottom row is dropped and the top row keeps its values
+    pub fn scroll_down(&mut self) {
+        if self.height < 2 { return }
+        self.pixels.copy_within(..self.width * (self.height - 1), self.width);
+    }
+}
+
+
+/// Every matrix effect needs a function to process the effect onto a canvas of width x height pixels.
+/// Works like *EffectProcessing*, but the effect knows the position of every pixel.
+pub trait CanvasProcessing: DynClone {
+
+    /// Defines how much mel points should be calculated for a canvas with the given size
+    fn n_mel(&self, width: usize, height: usize) -> usize;
+
+    /// Processes the effect for the mel points of the current frame.
+    /// Only called for effects of the frequency domain.
+    fn process_frequency(&mut self, _mel: &[i16], _canvas: &mut Canvas) {}
+
+    /// Processes the effect for the raw samples of the current frame.
+    /// Only called for effects of the time domain.
+    fn process_wave(&mut self, _wave: &[i16], _canvas: &mut Canvas) {}
+
+    /// Converts the canvas into the colours of the leds with the selected colour scheme.
+    /// By default the position of a pixel is its index inside the canvas.
+    fn colorize(&mut self, values: &[i16], _width: usize, scheme: &ColorScheme, pixels: &mut [Color]) {
+        scheme.apply(values, pixels)
+    }
+}
+
+
+/// Runs a canvas effect inside the worker, which only knows the flat output of the effects.
+#[derive(Clone)]
+pub(crate) struct CanvasEffect {
+    processor: Box<dyn CanvasProcessing + Send>,
+    width: usize
+}
+
+impl CanvasEffect {
+
+    pub(crate) fn new(processor: Box<dyn CanvasProcessing + Send>, width: usize) -> Self {
+        CanvasEffect { processor, width: width.max(1) }
+    }
+
+}
+
+impl EffectProcessing for CanvasEffect {
+
+    fn n_mel(&self, n_led: usize) -> usize {
+        self.processor.n_mel(self.width, n_led / self.width)
+    }
+
+    fn process_frequency(&mut self, mel: &[i16], output: &mut [i16]) {
+        self.processor.process_frequency(mel, &mut Canvas::new(self.width, output))
+    }
+
+    fn process_wave(&mut self, wave: &[i16], output: &mut [i16]) {
+        self.processor.process_wave(wave, &mut Canvas::new(self.width, output))
+    }
+
+    fn colorize(&mut self, values: &[i16], scheme: &ColorScheme, pixels: &mut [Color]) {
+        self.processor.colorize(values, self.width, scheme, pixels)
+    }
+}
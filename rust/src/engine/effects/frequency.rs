@@ -1,5 +1,9 @@
 use crate::engine::effects::EffectProcessing;
+use crate::engine::output::palette::ColorScheme;
+use crate::engine::output::pixel::Color;
 
+/// Mirrored spectrum: The lowest frequencies are in the middle of the strip
+/// and the higher frequencies spread to both ends.
 #[derive(Clone)]
 pub struct FrequencyEffect;
 
@@ -9,8 +13,30 @@ impl EffectProcessing for FrequencyEffect {
         n_led/2
     }
 
-    fn process_frequency(&self, input: &[i16], output: &mut [i16]) {
-        todo!()
+    fn process_frequency(&mut self, input: &[i16], output: &mut [i16]) {
+        let middle = output.len() / 2;
+        let (left, right) = output.split_at_mut(middle);
+
+        // Left half from the middle to the start, right half from the middle to the end
+        for (led, value) in left.iter_mut().rev().zip(input.iter()) {
+            *led = *value;
+        }
+        for (led, value) in right.iter_mut().zip(input.iter()) {
+            *led = *value;
+        }
     }
-}
 
+    /// The position of a led is its mel bin, so both halves get the same colours
+    fn colorize(&mut self, values: &[i16], scheme: &ColorScheme, pixels: &mut [Color]) {
+        let middle = values.len() / 2;
+        let steps = (values.len() - middle).max(2) - 1;
+
+        for (i, (pixel, value)) in pixels.iter_mut().zip(values.iter()).enumerate() {
+            let bin = match i < middle {
+                true => middle - 1 - i,
+                false => i - middle
+            };
+            *pixel = scheme.color(bin as f32 / steps as f32, *value);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::engine::effects::canvas::{Canvas, CanvasProcessing};
+use crate::engine::output::palette::ColorScheme;
+use crate::engine::output::pixel::Color;
+
+
+/// Spectrum bars: Every column shows the volume of one mel point as a bar from the bottom.
+/// The colour of a bar follows its height.
+#[derive(Clone)]
+pub struct SpectrumBarsEffect;
+
+impl CanvasProcessing for SpectrumBarsEffect {
+
+    fn n_mel(&self, width: usize, _height: usize) -> usize {
+        width
+    }
+
+    fn process_frequency(&mut self, mel: &[i16], canvas: &mut Canvas) {
+        let height = canvas.height();
+
+        for (x, value) in mel.iter().enumerate().take(canvas.width()) {
+            // Amount of lit pixels of the column
+            let level = (*value).max(0) as usize;
+            let bar = (level * height + i16::MAX as usize / 2) / i16::MAX as usize;
+
+            for y in 0..height {
+                let lit = height - y <= bar;
+                canvas.set(x, y, if lit { *value } else { 0 });
+            }
+        }
+    }
+
+    /// The position of a pixel is its height, so the bars change the colour from the bottom to the top
+    fn colorize(&mut self, values: &[i16], width: usize, scheme: &ColorScheme, pixels: &mut [Color]) {
+        let height = values.len() / width.max(1);
+        let steps = (height.max(2) - 1) as f32;
+
+        for (i, (pixel, value)) in pixels.iter_mut().zip(values.iter()).enumerate() {
+            let y = i / width.max(1);
+            *pixel = scheme.color(height.saturating_sub(y + 1) as f32 / steps, *value);
+        }
+    }
+}
+
+
+/// Spectrogram waterfall: The current spectrum enters the canvas as the top row
+/// and moves one row further down with each frame.
+#[derive(Clone)]
+pub struct SpectrogramEffect;
+
+impl CanvasProcessing for SpectrogramEffect {
+
+    fn n_mel(&self, width: usize, _height: usize) -> usize {
+        width
+    }
+
+    fn process_frequency(&mut self, mel: &[i16], canvas: &mut Canvas) {
+        if canvas.height() == 0 { return }
+
+        // The canvas still contains the last frame, so it only needs to be shifted
+        canvas.scroll_down();
+        for (pixel, value) in canvas.row_mut(0).iter_mut().zip(mel.iter()) {
+            *pixel = *value;
+        }
+    }
+
+    /// The position of a pixel is its column, so every frequency keeps its colour while it moves down
+    fn colorize(&mut self, values: &[i16], width: usize, scheme: &ColorScheme, pixels: &mut [Color]) {
+        let steps = (width.max(2) - 1) as f32;
+
+        for (i, (pixel, value)) in pixels.iter_mut().zip(values.iter()).enumerate() {
+            *pixel = scheme.color((i % width.max(1)) as f32 / steps, *value);
+        }
+    }
+}
+
+
+/// Radial spectrum: The lowest frequencies are in the centre of the canvas
+/// and the higher frequencies spread in rings to the corners.
+#[derive(Clone)]
+pub struct RadialSpectrumEffect;
+
+impl RadialSpectrumEffect {
+
+    /// Distance of the pixel to the centre relative to the distance of the corners
+    fn radius(x: usize, y: usize, width: usize, height: usize) -> f32 {
+        let dx = x as f32 + 0.5 - width as f32 / 2.0;
+        let dy = y as f32 + 0.5 - height as f32 / 2.0;
+        let max = ((width as f32 / 2.0).powi(2) + (height as f32 / 2.0).powi(2)).sqrt();
+
+        ((dx * dx + dy * dy).sqrt() / max).min(1.0)
+    }
+
+}
+
+impl CanvasProcessing for RadialSpectrumEffect {
+
+    /// One mel point per ring, the rings have the width of one pixel
+    fn n_mel(&self, width: usize, height: usize) -> usize {
+        let diagonal = ((width * width + height * height) as f32).sqrt();
+        (diagonal / 2.0).ceil().max(1.0) as usize
+    }
+
+    fn process_frequency(&mut self, mel: &[i16], canvas: &mut Canvas) {
+        if mel.is_empty() { return }
+        let (width, height) = (canvas.width(), canvas.height());
+
+        for y in 0..height {
+            for x in 0..width {
+                let ring = (Self::radius(x, y, width, height) * mel.len() as f32) as usize;
+                canvas.set(x, y, mel[ring.min(mel.len() - 1)]);
+            }
+        }
+    }
+
+    /// The position of a pixel is its distance to the centre
+    fn colorize(&mut self, values: &[i16], width: usize, scheme: &ColorScheme, pixels: &mut [Color]) {
+        let width = width.max(1);
+        let height = values.len() / width;
+
+        for (i, (pixel, value)) in pixels.iter_mut().zip(values.iter()).enumerate() {
+            *pixel = scheme.color(Self::radius(i % width, i / width, width, height), *value);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crate::engine::effects::EffectProcessing;
+
+/// Oscilloscope: Every led shows the amplitude of the wave at its position.
+#[derive(Clone)]
+pub struct OscilloscopeEffect;
+
+impl EffectProcessing for OscilloscopeEffect {
+
+    fn n_mel(&self, _n_led: usize) -> usize {
+        0
+    }
+
+    fn process_wave(&mut self, wave: &[i16], output: &mut [i16]) {
+        if wave.is_empty() { return }
+
+        let n_led = output.len();
+        for (i, led) in output.iter_mut().enumerate() {
+            let position = i * wave.len() / n_led;
+            *led = wave[position].saturating_abs();
+        }
+    }
+}
+
+
+/// Waveform scroll: The loudest sample of every frame enters the strip at the start
+/// and moves one led further with each frame.
+#[derive(Clone)]
+pub struct WaveformScrollEffect;
+
+impl EffectProcessing for WaveformScrollEffect {
+
+    fn n_mel(&self, _n_led: usize) -> usize {
+        0
+    }
+
+    fn process_wave(&mut self, wave: &[i16], output: &mut [i16]) {
+        if output.is_empty() { return }
+
+        // The output still contains the last frame, so it only needs to be shifted
+        output.copy_within(..output.len()-1, 1);
+        output[0] = wave.iter()
+            .map(|sample| sample.saturating_abs())
+            .max()
+            .unwrap_or(0);
+    }
+}
+
+
+/// Amplitude envelope: The volume fills the strip from the middle to both ends like a vu meter.
+/// The envelope rises fast and falls slowly, so the leds don't flicker.
+#[derive(Clone)]
+pub struct EnvelopeEffect {
+    envelope: f32
+}
+
+impl EnvelopeEffect {
+    /// Part of the difference, which is taken over when the volume rises
+    const ATTACK: f32 = 0.8;
+    /// Part of the difference, which is taken over when the volume falls
+    const DECAY: f32 = 0.1;
+
+    pub fn new() -> Self {
+        EnvelopeEffect { envelope: 0.0 }
+    }
+}
+
+impl Default for EnvelopeEffect {
+    fn default() -> Self {
+        EnvelopeEffect::new()
+    }
+}
+
+impl EffectProcessing for EnvelopeEffect {
+
+    fn n_mel(&self, _n_led: usize) -> usize {
+        0
+    }
+
+    fn process_wave(&mut self, wave: &[i16], output: &mut [i16]) {
+        if wave.is_empty() { return }
+
+        // Root mean square of the frame between 0 and 1
+        let rms = (wave.iter().map(|&sample| (sample as f32).powi(2)).sum::<f32>() / wave.len() as f32)
+            .sqrt() / i16::MAX as f32;
+
+        let factor = if rms > self.envelope { Self::ATTACK } else { Self::DECAY };
+        self.envelope += (rms - self.envelope) * factor;
+
+        // Amount of leds from the middle to each end
+        let middle = output.len() as f32 / 2.0;
+        let reach = (self.envelope.min(1.0) * middle).round();
+
+        for (i, led) in output.iter_mut().enumerate() {
+            let distance = (i as f32 + 0.5 - middle).abs();
+            *led = if distance < reach { i16::MAX } else { 0 };
+        }
+    }
+}
@@ -2,11 +2,13 @@ use std::fmt::{Debug, Error};
 use log::error;
 use thiserror::Error;
 
+use super::sender::UniverseRange;
+
 #[derive(Error, Debug)]
 pub enum SenderError {
 
-    /// Maximum packet size, which is defined as 512
-    #[error("The maximum packet size of 512 bytes was reached.")]
+    /// The data doesn't fit into the available universes
+    #[error("The data doesn't fit into the available universes.")]
     WrongPacketSize,
 
     /// Error occurred in SenderInner.send_packet() function
@@ -15,7 +17,23 @@ pub enum SenderError {
 
     /// Error occurred in new_source() function
     #[error("Error occurred while sender creation {0}")]
-    CreationError(String)
+    CreationError(String),
+
+    /// The channels per universe must be between 1 and 512
+    #[error("{0} channels per universe are invalid.")]
+    InvalidChannelsPerUniverse(usize),
+
+    /// The universes are outside of the sacn universes 1 to 63999
+    #[error("The universes {0:?} are invalid.")]
+    InvalidUniverses(UniverseRange),
+
+    /// The universes are already used by the sender or one of its clones
+    #[error("The universes {0:?} are already used by another sender.")]
+    UniverseOverlap(UniverseRange),
+
+    /// The frame rate of the sender must be larger than 0
+    #[error("The frame rate {0} is invalid.")]
+    InvalidFrameRate(u32)
 
 }
 
@@ -40,10 +58,45 @@ pub enum ApplicationError {
     #[error("No input device was selected.")]
     NoDeviceSelected,
 
+    /// No device matches the name or the position
+    #[error("The input device {0} wasn't found.")]
+    DeviceNotFound(String),
+
     /// Maximum amount of parallel engines was reached
     #[error("Maximum amount of possible engines was reached.")]
     MaximumEngines,
 
+    /// The format of an audio source can't be processed
+    #[error("The audio format {0} is not supported.")]
+    UnsupportedFormat(String),
+
+    /// The frequency range of the mel filterbank is invalid
+    #[error("The frequency range from {min} Hz to {max} Hz is invalid.")]
+    InvalidFrequencyRange {
+        min: f32,
+        max: f32
+    },
+
+    /// The coefficient of the pre emphasis filter is invalid
+    #[error("The pre emphasis coefficient {0} is outside of 0 and 1.")]
+    InvalidPreEmphasis(f32),
+
+    /// The configuration of the input path can't be used
+    #[error("Invalid input configuration: {0}")]
+    InvalidInputConfig(String),
+
+    /// The configuration of the output path can't be used
+    #[error("Invalid output configuration: {0}")]
+    InvalidOutputConfig(String),
+
+    /// The led layout doesn't fit to its canvas
+    #[error("Invalid led layout: {0}")]
+    InvalidLayout(String),
+
+    /// The colour palette can't be used
+    #[error("Invalid palette: {0}")]
+    InvalidPalette(String),
+
     /// No current input stream is available
     #[error("No input stream selected.")]
     NoInputStream,
@@ -0,0 +1,63 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use super::input::DeviceInfo;
+
+
+/// Events of the engine, which can be received by a UI or a control API
+#[derive(Clone, Debug, PartialEq)]
+pub enum EngineEvent {
+    /// The input stream reported an error
+    StreamError(String),
+    /// The stream failed and the default device with the name is used instead
+    FallbackDevice(String),
+    /// The stream failed and no other device is available, so a silent source is used instead
+    FallbackSilence,
+    /// The originally selected device with the name is used again
+    DeviceRecovered(String)
+}
+
+
+/// Changes of the available input devices
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeviceEvent {
+    /// A new device was plugged in
+    Added(DeviceInfo),
+    /// The device was removed
+    Removed(DeviceInfo),
+    /// Another device is the default device now. None, if no default device exists anymore
+    DefaultChanged(Option<DeviceInfo>)
+}
+
+
+/// Distributes the events to all subscribers
+pub struct EventBus<E: Clone = EngineEvent> {
+    subscribers: Vec<Sender<E>>
+}
+
+impl<E: Clone> Default for EventBus<E> {
+    fn default() -> Self {
+        EventBus { subscribers: vec![] }
+    }
+}
+
+impl<E: Clone> EventBus<E> {
+
+    pub fn new() -> Self {
+        EventBus::default()
+    }
+
+    /// Register a new subscriber, which receives every following event
+    pub fn subscribe(&mut self) -> Receiver<E> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+
+        receiver
+    }
+
+    /// Send the event to all subscribers.
+    /// Subscribers which dropped their receiver are removed.
+    pub fn emit(&mut self, event: E) {
+        self.subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+    }
+
+}
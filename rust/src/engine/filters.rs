@@ -7,7 +7,9 @@ use super::utils::Domain;
 dyn_clone::clone_trait_object!(FilterProcessing);
 pub trait FilterProcessing: DynClone {
 
-    fn process(&self, data: &mut [i16]);
+    /// Filters the data in place.
+    /// The filter is used for one stream, so it can keep its state between the frames.
+    fn process(&mut self, data: &mut [i16]);
 
 }
 
@@ -62,13 +64,45 @@ impl Filter {
 
 
 
-// Example filter
-
+/// First order pre emphasis filter: y[n] = x[n] - a*x[n-1]
+/// Lowers the amplitude of low frequencies, so the higher frequencies become visible after the fourier transformation.
 #[derive(Clone)]
-pub struct SimplePreEmphasisFilter;
+pub struct SimplePreEmphasisFilter {
+    /// Coefficient *a* of the filter, mostly between 0.9 and 1
+    coefficient: f32,
+    /// Last sample of the previous frame
+    last: i16
+}
+
+impl SimplePreEmphasisFilter {
+    pub const DEFAULT_COEFFICIENT: f32 = 0.9;
+
+    pub fn new(coefficient: f32) -> Self {
+        SimplePreEmphasisFilter {
+            coefficient,
+            last: 0
+        }
+    }
+
+    pub fn coefficient(&self) -> f32 {
+        self.coefficient
+    }
+}
+
+impl Default for SimplePreEmphasisFilter {
+    fn default() -> Self {
+        SimplePreEmphasisFilter::new(Self::DEFAULT_COEFFICIENT)
+    }
+}
 
 impl FilterProcessing for SimplePreEmphasisFilter {
-    fn process(&self, data: &mut [i16]) {
-        todo!()
+    fn process(&mut self, data: &mut [i16]) {
+        for sample in data.iter_mut() {
+            let current = *sample;
+
+            // The cast to i16 saturates the result
+            *sample = (current as f32 - self.coefficient * self.last as f32) as i16;
+            self.last = current;
+        }
     }
 }
@@ -5,15 +5,194 @@ use super::errors::ApplicationError;
 use crate::engine::utils::{AudioBuffer, BufferInfo};
 use crate::ok_or_skip;
 
-use cpal::{DefaultStreamConfigError, Device, Host, InputCallbackInfo, Stream, StreamConfig, StreamError};
+use cpal::{DefaultStreamConfigError, Device, Host, InputCallbackInfo, SampleFormat, Stream, StreamConfig, StreamError};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 
+pub mod file;
+pub mod generator;
+pub mod store;
+pub mod watcher;
+mod format;
+mod playback;
+mod resample;
+
+pub use format::{ChannelStrategy, InputSample};
+pub use playback::Playback;
+use format::ChannelMixer;
+use resample::Resampler;
+
 
 // The default frame on WASAPI is 100 FPS.
-const CAPTURE_FRAME_RATE: u32 = 100;
-// The default streaming rate
-const DISPLAY_FRAME_RATE: u32 = 50;
-//the difference has to be an int.
+pub const DEFAULT_CAPTURE_FRAME_RATE: u32 = 100;
+// The default streaming rate, the capture rate must be a multiple of it
+pub const DEFAULT_DISPLAY_FRAME_RATE: u32 = 50;
+
+
+/// Configuration of the input path, which is the same for every input source.
+/// Defines how the samples are framed into windows before they reach the worker.
+#[derive(Copy, Clone, Debug)]
+pub struct InputConfig {
+    /// Amount of captured frames per second.
+    /// Must be a multiple of the display frame rate.
+    pub capture_frame_rate: u32,
+
+    /// Amount of frames per second, which are displayed on the leds
+    pub display_frame_rate: u32,
+
+    /// Amount of samples in one window.
+    /// Without a value the window contains all captured frames of one displayed frame.
+    pub window_length: Option<usize>,
+
+    /// Amount of new samples between two windows.
+    /// Without a value one window per displayed frame is created.
+    pub hop_length: Option<usize>,
+
+    /// Defines how the channels of the source are combined
+    pub channels: ChannelStrategy,
+
+    /// Internal sample rate, to which every source is resampled.
+    /// Without a value the sample rate of the source is used.
+    pub sample_rate: Option<u32>
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        InputConfig {
+            capture_frame_rate: DEFAULT_CAPTURE_FRAME_RATE,
+            display_frame_rate: DEFAULT_DISPLAY_FRAME_RATE,
+            window_length: None,
+            hop_length: None,
+            channels: ChannelStrategy::default(),
+            sample_rate: None
+        }
+    }
+}
+
+impl InputConfig {
+
+    /// Check if the configuration can be used
+    pub fn validate(&self) -> Result<()> {
+        if self.capture_frame_rate == 0 || self.display_frame_rate == 0 {
+            Err(ApplicationError::InvalidInputConfig(String::from("The frame rates must be larger than 0")))?
+        }
+        if self.capture_frame_rate < self.display_frame_rate {
+            Err(ApplicationError::InvalidInputConfig(format!(
+                "The capture frame rate ({}) must not be smaller than the display frame rate ({})",
+                self.capture_frame_rate, self.display_frame_rate
+            )))?
+        }
+        // Every displayed frame must contain the same amount of captured frames
+        match self.capture_frame_rate % self.display_frame_rate {
+            0 => (),
+            _ => Err(ApplicationError::InvalidInputConfig(format!(
+                "The capture frame rate ({}) must be a multiple of the display frame rate ({})",
+                self.capture_frame_rate, self.display_frame_rate
+            )))?
+        }
+        if self.window_length == Some(0) {
+            Err(ApplicationError::InvalidInputConfig(String::from("The window length must be larger than 0")))?
+        }
+        if self.hop_length == Some(0) {
+            Err(ApplicationError::InvalidInputConfig(String::from("The hop length must be larger than 0")))?
+        }
+        if self.sample_rate == Some(0) {
+            Err(ApplicationError::InvalidInputConfig(String::from("The sample rate must be larger than 0")))?
+        }
+
+        Ok(())
+    }
+
+    /// Describes how the samples of a source with the given sample rate will be buffered
+    pub fn buffer_info(&self, sample_rate: u32) -> Result<BufferInfo> {
+        self.validate()?;
+        let sample_rate = self.sample_rate.unwrap_or(sample_rate);
+
+        // The frame length defines a pack of samples. We need as much frames as in FRAME_RATE declared.
+        // So we split the Samples to the FRAME_RATE
+        let frame_length = (sample_rate / self.capture_frame_rate) as usize;
+        if frame_length == 0 {
+            Err(ApplicationError::InvalidInputConfig(format!(
+                "The capture frame rate ({}) is too high for the sample rate ({})",
+                self.capture_frame_rate, sample_rate
+            )))?
+        }
+
+        // By default a window contains all captured frames of one displayed frame.
+        // E.g. the capture rate is 100fps and the streaming rate is 50fps. So we need 2 frames for each window.
+        let window_length = self.window_length
+            .unwrap_or(frame_length * (self.capture_frame_rate / self.display_frame_rate) as usize);
+        let hop_length = self.hop_length
+            .unwrap_or((sample_rate / self.display_frame_rate) as usize);
+
+        Ok(BufferInfo {
+            window_length,
+            hop_length,
+            sample_rate,
+            channels: self.channels.output_channels()
+        })
+    }
+
+}
+
+
+/// Callback which receives the buffered audio data of a stream.
+/// Every channel of a window follows after the previous one, see *BufferInfo*.
+pub type DataCallback = Box<dyn FnMut(&[i16]) + Send + 'static>;
+
+/// Callback which will be called if an error occurs inside a stream
+pub type ErrorCallback = Box<dyn FnMut(StreamError) + Send + 'static>;
+
+
+/// Abstraction over everything which can deliver audio data to the engine.
+/// The pc's audio input is only one possibility, files or generated signals can be used as well.
+pub trait InputSource {
+
+    /// Gets all current available devices
+    fn available_devices(&self) -> Result<Vec<DeviceInfo>>;
+
+    /// Set device at position as current device.
+    /// The position can change, when devices are added or removed. Prefer *set_device_by_name*.
+    fn set_device(&mut self, position: usize) -> Result<()>;
+
+    /// Set the device, which matches the name best as current device, see *find_device*
+    fn set_device_by_name(&mut self, name: &str) -> Result<()> {
+        let devices = self.available_devices()?;
+        let device = find_device(&devices, name)
+            .ok_or_else(|| ApplicationError::DeviceNotFound(name.to_string()))?;
+
+        self.set_device(device.position)
+    }
+
+    /// Set the default device of the source as current device
+    fn set_default_device(&mut self) -> Result<()> {
+        let devices = self.available_devices()?;
+        let device = devices.iter()
+            .find(|device| device.standard)
+            .ok_or_else(|| ApplicationError::DeviceNotFound(String::from("default")))?;
+
+        self.set_device(device.position)
+    }
+
+    /// Name of the current device
+    fn current_device_name(&self) -> Result<String>;
+
+    /// Sample rate of the current device
+    fn sample_rate(&self) -> Result<u32>;
+
+    /// Describes how the audio data will be buffered before the callback is called
+    fn buffer_info(&self, config: &InputConfig) -> Result<BufferInfo> {
+        config.buffer_info(self.sample_rate()?)
+    }
+
+    /// Build a new mono stream, which calls the callback with every buffered window
+    fn build_stream(&mut self, config: &InputConfig, callback: DataCallback, error_callback: ErrorCallback) -> Result<()>;
+
+    /// Start the current stream
+    fn start_stream(&self) -> Result<()>;
+
+    /// Stop the current stream
+    fn pause_stream(&self) -> Result<()>;
+}
 
 
 
@@ -47,16 +226,62 @@ const DISPLAY_FRAME_RATE: u32 = 50;
             }
         }
 
-        /// Get the current device name
-        pub fn current_device_name(&self) -> Result<String> {
-            match &self.device {
-                None => Err(ApplicationError::NoDeviceSelected)?,
-                Some(device) => Ok(device.safe_name())
+        /// Build a new input stream with the device configuration, which is framed like the input config defines.
+        /// The samples of the device are converted to i16 and the channels are mixed with the channel strategy.
+        pub fn build_mono_stream<C, E>(
+            &mut self,
+            config: &InputConfig,
+            callback: C,
+            error_callback: E
+        ) -> Result<()>
+            where
+                C: FnMut(&[i16], &InputCallbackInfo) + Send + 'static,
+                E: FnMut(StreamError) + Send + 'static
+         {
+             // Return a NoDevice error when no device will be found or a DefaultStreamConfigError if no configuration will be found
+             let device = self.current_device()?;
+             let configuration = device.supported_stream_configuration()?;
+             let format = device.supported_sample_format()?;
+
+             let info = self.buffer_info(config)?;
+             let mixer = ChannelMixer::new(config.channels, configuration.channels)?;
+             let resampler = Resampler::new(configuration.sample_rate.0, info.sample_rate, info.channels);
+             let buffer = AudioBuffer::from_info(info);
+
+            // cpal only delivers the formats of the device, so every format needs its own stream
+            let stream = match format {
+                SampleFormat::I16 => build_input_stream::<i16, C, E>(device, &configuration, mixer, resampler, buffer, callback, error_callback)?,
+                SampleFormat::U16 => build_input_stream::<u16, C, E>(device, &configuration, mixer, resampler, buffer, callback, error_callback)?,
+                SampleFormat::F32 => build_input_stream::<f32, C, E>(device, &configuration, mixer, resampler, buffer, callback, error_callback)?
+            };
+            self.input_stream = Some(stream);
+
+            Ok(())
+        }
+
+        /// Get the current device
+        fn current_device(&self) -> Result<&Device> {
+            self.device.as_ref()
+                .context(ApplicationError::NoDeviceSelected)
+        }
+
+        /// Get the default name for the input device.
+        fn default_device_name(&self) -> Option<String> {
+            match self.host.default_input_device() {
+                None => None,
+                Some(device) => Some(device.safe_name())
             }
         }
 
+
+    }
+
+
+
+    impl InputSource for DeviceInputSource {
+
         /// Gets all current available devices
-        pub fn available_devices(&self) -> Result<Vec<DeviceInfo>> {
+        fn available_devices(&self) -> Result<Vec<DeviceInfo>> {
             let mut vec: Vec<DeviceInfo> = vec![];
 
             let devices = self.host.input_devices()?;
@@ -89,35 +314,54 @@ const DISPLAY_FRAME_RATE: u32 = 50;
         }
 
         /// Set device at position as current device
-        pub fn set_device(&mut self, position: usize) -> Result<()> {
+        fn set_device(&mut self, position: usize) -> Result<()> {
             let mut devices = self.host.input_devices()?;
 
-            let device = devices.nth(position);
-            Ok(self.device = device)
+            let device = devices.nth(position)
+                .ok_or_else(|| ApplicationError::DeviceNotFound(format!("at position {}", position)))?;
+            self.device = Some(device);
+
+            Ok(())
         }
 
-        pub fn buffer_info(&self) -> Result<BufferInfo> {
-            let device = self.device.as_ref()
-                .ok_or(ApplicationError::NoDeviceSelected)?;
+        /// Search the device directly, so the position can't change in between
+        fn set_device_by_name(&mut self, name: &str) -> Result<()> {
+            let devices = self.host.input_devices()?
+                .map(|device| (device.safe_name(), device));
 
-            let config = device.supported_stream_configuration()?;
+            let device = best_match(devices, name)
+                .ok_or_else(|| ApplicationError::DeviceNotFound(name.to_string()))?;
+            self.device = Some(device);
 
-            // The frame length defines a pack of samples. We need as much frames as in FRAME_RATE declared.
-            // So we split the Samples to the FRAME_RATE
-            let frame_length = (config.sample_rate.0 / CAPTURE_FRAME_RATE) as usize;
+            Ok(())
+        }
 
-            // Frame capture_size defines how many frames will be captured in 1 period
-            // The display rate is set to 100fps and the streaming rate is 50fps. So we need 2 frames for each period.
-            let frame_capture_size = (CAPTURE_FRAME_RATE/DISPLAY_FRAME_RATE) as usize;
+        fn set_default_device(&mut self) -> Result<()> {
+            let device = self.host.default_input_device()
+                .ok_or_else(|| ApplicationError::DeviceNotFound(String::from("default")))?;
+            self.device = Some(device);
 
-            Ok(
-                BufferInfo { frame_length, frame_capture_size }
-            )
+            Ok(())
+        }
+
+        /// Get the current device name
+        fn current_device_name(&self) -> Result<String> {
+            match &self.device {
+                None => Err(ApplicationError::NoDeviceSelected)?,
+                Some(device) => Ok(device.safe_name())
+            }
+        }
+
+        fn sample_rate(&self) -> Result<u32> {
+            let device = self.current_device()?;
+            let config = device.supported_stream_configuration()?;
+
+            Ok(config.sample_rate.0)
         }
 
 
         /// Start the current stream
-        pub fn start_stream(&self) -> Result<()> {
+        fn start_stream(&self) -> Result<()> {
             match &self.input_stream {
                 // Return NoStream if no stream is selected
                 None => Err(ApplicationError::NoInputStream)?,
@@ -126,7 +370,7 @@ const DISPLAY_FRAME_RATE: u32 = 50;
         }
 
         /// Stop the current stream
-        pub fn pause_stream(&self) -> Result<()> {
+        fn pause_stream(&self) -> Result<()> {
             match &self.input_stream {
                 // Return NoStream if no stream is selected
                 None => Err(ApplicationError::NoInputStream)?,
@@ -134,85 +378,60 @@ const DISPLAY_FRAME_RATE: u32 = 50;
             }
         }
 
-
-        /// Build a new input stream with a fixed configuration, frame_length and frame_capture_size
-        /// Returns the size of one frame
-        pub fn build_mono_stream<C, E>(
-            &mut self,
-            mut callback: C,
-            mut error_callback: E
-        ) -> Result<()>
-            where
-                C: FnMut(&[i16], &InputCallbackInfo) + Send + 'static,
-                E: FnMut(StreamError) + Send + 'static
-         {
-             // Return a NoDevice error when no device will be found or a DefaultStreamConfigError if no configuration will be found
-             let device = self.current_device()?;
-             let configuration = device.supported_stream_configuration()?;
-
-
-             let mut buffer = AudioBuffer::from_info(self.buffer_info()?);
-             let mut buffer_step = false;
-
-            self.input_stream = Some(device.build_input_stream(
-                &configuration,
-                move |data: &[i16], info: &InputCallbackInfo| {
-
-                    // Creates an iterator which only collects one channel
-                    let iter = data.iter().step_by(2).cloned();
-
-                    match buffer_step {
-                        false => {
-                            buffer.data.splice(..buffer.frame_length(), iter);
-                        }
-                        true => {
-                            buffer.data.splice(buffer.frame_length().., iter);
-                            callback(buffer.as_slice(), info)
-                        }
-                    }
-
-
-                    //TODO New buffer algorithm
-                    //for (i, value) in data.iter().step_by(2).enumerate() {
-                    //    buffer[i/2] = *value
-                    //}
-
-                    buffer_step = !buffer_step;
-                },
-                move |error: StreamError| {
-                    error_callback(error)
-                })?
-            
-            );
-
-            Ok(())
+        fn build_stream(&mut self, config: &InputConfig, mut callback: DataCallback, error_callback: ErrorCallback) -> Result<()> {
+            self.build_mono_stream(
+                config,
+                move |data, _| callback(data),
+                error_callback
+            )
         }
 
-        /// Get the current device
-        fn current_device(&self) -> Result<&Device> {
-            self.device.as_ref()
-                .context(ApplicationError::NoDeviceSelected)
-        }
+    }
 
-        /// Get the default name for the input device.
-        fn default_device_name(&self) -> Option<String> {
-            match self.host.default_input_device() {
-                None => None,
-                Some(device) => Some(device.safe_name())
-            }
-        }
 
+    /// Build an input stream for devices, which deliver samples of the type T
+    fn build_input_stream<T, C, E>(
+        device: &Device,
+        configuration: &StreamConfig,
+        mixer: ChannelMixer,
+        mut resampler: Resampler,
+        mut buffer: AudioBuffer<i16>,
+        mut callback: C,
+        mut error_callback: E
+    ) -> Result<Stream>
+        where
+            T: InputSample + cpal::Sample,
+            C: FnMut(&[i16], &InputCallbackInfo) + Send + 'static,
+            E: FnMut(StreamError) + Send + 'static
+    {
+        let mut mixed = vec![];
+        let mut resampled = vec![];
+
+        let stream = device.build_input_stream(
+            configuration,
+            move |data: &[T], info: &InputCallbackInfo| {
+                mixer.mix(data, &mut mixed);
+                resampler.process(&mixed, &mut resampled);
+
+                buffer.push(resampled.iter().cloned(), |frame| callback(frame, info));
+            },
+            move |error: StreamError| {
+                error_callback(error)
+            }
+        )?;
 
+        Ok(stream)
     }
 
 
-
     /// Utilities to interact better with the device.
     trait DeviceUtilities {
         /// Get the name of with <Unknown> instead of an error
         fn safe_name(&self) -> String;
         /// Get the supported stream config
         fn supported_stream_configuration(&self) -> Result<StreamConfig, DefaultStreamConfigError>;
+        /// Get the sample format of the supported stream config
+        fn supported_sample_format(&self) -> Result<SampleFormat, DefaultStreamConfigError>;
     }
 
     impl DeviceUtilities for Device {
@@ -229,11 +448,17 @@ const DISPLAY_FRAME_RATE: u32 = 50;
             Ok(configuration.config())
         }
 
+        fn supported_sample_format(&self) -> Result<SampleFormat, DefaultStreamConfigError> {
+            let configuration = self.default_input_config()?;
+            Ok(configuration.sample_format())
+        }
+
     }
 
 
 /// Describes a device with all necessary information's to decide,
 /// which device should be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DeviceInfo {
     /// The position of the device referred to the host.
     pub position: usize,
@@ -252,3 +477,50 @@ pub struct DeviceInfo {
 }
 
 
+/// Find the device, which matches the name best.
+/// An exact name is preferred over a name which contains the searched name.
+/// Otherwise every word of the searched name must be part of the device name.
+/// The comparison ignores the case.
+pub fn find_device<'a>(devices: &'a [DeviceInfo], name: &str) -> Option<&'a DeviceInfo> {
+    best_match(devices.iter().map(|device| (device.name.clone(), device)), name)
+}
+
+/// Get the item with the best matching name. The first item wins if multiple items match equally.
+fn best_match<T>(items: impl Iterator<Item = (String, T)>, name: &str) -> Option<T> {
+    let mut best: Option<(u8, T)> = None;
+
+    for (item_name, item) in items {
+        let score = match match_score(&item_name, name) {
+            None => continue,
+            Some(value) => value
+        };
+
+        let better = match &best {
+            None => true,
+            Some((best_score, _)) => score > *best_score
+        };
+        if better {
+            best = Some((score, item));
+        }
+    }
+
+    best.map(|(_, item)| item)
+}
+
+/// Rate how well the device name matches the searched name. Higher is better.
+fn match_score(device_name: &str, name: &str) -> Option<u8> {
+    let device_name = device_name.to_lowercase();
+    let name = name.trim().to_lowercase();
+
+    if name.is_empty() {
+        None
+    } else if device_name == name {
+        Some(3)
+    } else if device_name.contains(&name) {
+        Some(2)
+    } else if name.split_whitespace().all(|word| device_name.contains(word)) {
+        Some(1)
+    } else {
+        None
+    }
+}
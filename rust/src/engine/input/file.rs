@@ -0,0 +1,197 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Result;
+use hound::{SampleFormat, WavReader};
+
+use crate::engine::errors::ApplicationError;
+use super::{DataCallback, DeviceInfo, ErrorCallback, InputConfig, InputSource};
+use super::format::ChannelMixer;
+use super::playback::{Playback, PlaybackStream};
+
+
+/// Input source which plays a PCM wav file instead of listening to an audio device.
+/// The whole file is decoded to interleaved i16 samples when it is opened,
+/// so every new stream starts at the beginning of the file again.
+pub struct WavInputSource {
+    /// Path to the played file
+    path: PathBuf,
+
+    /// Decoded interleaved samples of the file
+    samples: Arc<Vec<i16>>,
+
+    /// Amount of channels inside the file
+    channels: u16,
+
+    /// Sample rate of the file
+    sample_rate: u32,
+
+    /// Real time or as fast as possible
+    playback: Playback,
+
+    /// Own playback stream
+    stream: Option<PlaybackStream>
+}
+
+
+impl WavInputSource {
+
+    /// Open and decode the wav file at the given path.
+    /// Supported are 8, 16, 24 and 32 bit integer and 32 bit float samples with any amount of channels.
+    pub fn open<P: AsRef<Path>>(path: P, playback: Playback) -> Result<Self> {
+        let mut reader = WavReader::open(path.as_ref())?;
+        let spec = reader.spec();
+
+        if spec.channels == 0 {
+            Err(ApplicationError::UnsupportedFormat(
+                format!("{} channels", spec.channels)
+            ))?
+        }
+
+        // Read all samples as i16
+        let samples: Vec<i16> = match (spec.sample_format, spec.bits_per_sample) {
+            (SampleFormat::Int, 8 | 16 | 24 | 32) => {
+                let bits = spec.bits_per_sample;
+                reader.samples::<i32>()
+                    .map(|sample| sample.map(|value| scale_to_i16(value, bits)))
+                    .collect::<Result<_, _>>()?
+            }
+            (SampleFormat::Float, 32) => {
+                reader.samples::<f32>()
+                    .map(|sample| sample.map(|value| (value * i16::MAX as f32) as i16))
+                    .collect::<Result<_, _>>()?
+            }
+            (format, bits) => Err(ApplicationError::UnsupportedFormat(
+                format!("{:?} with {} bits", format, bits)
+            ))?
+        };
+
+        Ok(WavInputSource {
+            path: path.as_ref().to_path_buf(),
+            samples: Arc::new(samples),
+            channels: spec.channels,
+            sample_rate: spec.sample_rate,
+            playback,
+            stream: None
+        })
+    }
+
+    /// Duration of the file in samples per channel
+    pub fn len(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    /// Check if the file doesn't contain any samples
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Block until the whole file was played.
+    /// Has to be called after the stream was started, otherwise it won't return.
+    pub fn wait(&mut self) -> Result<()> {
+        match &mut self.stream {
+            None => Err(ApplicationError::NoInputStream)?,
+            Some(stream) => {
+                stream.wait();
+                Ok(())
+            }
+        }
+    }
+
+    /// Name of the file, which is used as device name
+    fn file_name(&self) -> String {
+        match self.path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => String::from("<Unknown>")
+        }
+    }
+
+}
+
+
+impl InputSource for WavInputSource {
+
+    /// The file is the only available device
+    fn available_devices(&self) -> Result<Vec<DeviceInfo>> {
+        Ok(vec![
+            DeviceInfo {
+                position: 0,
+                name: self.file_name(),
+                channels: self.channels,
+                sample_rate: self.sample_rate,
+                standard: true
+            }
+        ])
+    }
+
+    fn set_device(&mut self, position: usize) -> Result<()> {
+        if position != 0 {
+            Err(ApplicationError::DeviceNotFound(format!("at position {}", position)))?
+        }
+        Ok(())
+    }
+
+    fn current_device_name(&self) -> Result<String> {
+        Ok(self.file_name())
+    }
+
+    fn sample_rate(&self) -> Result<u32> {
+        Ok(self.sample_rate)
+    }
+
+    fn build_stream(&mut self, config: &InputConfig, callback: DataCallback, _error_callback: ErrorCallback) -> Result<()> {
+        // Drop the old stream before the new one starts
+        self.stream = None;
+
+        let samples = self.samples.clone();
+        let mut position = 0;
+
+        self.stream = Some(PlaybackStream::new(
+            self.buffer_info(config)?,
+            self.sample_rate,
+            self.playback,
+            ChannelMixer::new(config.channels, self.channels)?,
+            move |chunk: &mut [i16]| {
+                let length = chunk.len().min(samples.len() - position);
+                chunk[..length].copy_from_slice(&samples[position..position+length]);
+
+                position += length;
+                length
+            },
+            callback
+        ));
+
+        Ok(())
+    }
+
+    fn start_stream(&self) -> Result<()> {
+        match &self.stream {
+            None => Err(ApplicationError::NoInputStream)?,
+            Some(stream) => {
+                stream.play();
+                Ok(())
+            }
+        }
+    }
+
+    fn pause_stream(&self) -> Result<()> {
+        match &self.stream {
+            None => Err(ApplicationError::NoInputStream)?,
+            Some(stream) => {
+                stream.pause();
+                Ok(())
+            }
+        }
+    }
+
+}
+
+
+/// Scale an integer sample with the given bit depth to the i16 range
+fn scale_to_i16(value: i32, bits: u16) -> i16 {
+    if bits > 16 {
+        (value >> (bits - 16)) as i16
+    } else {
+        (value << (16 - bits)) as i16
+    }
+}
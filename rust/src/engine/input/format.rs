@@ -0,0 +1,118 @@
+use anyhow::Result;
+
+use crate::engine::errors::ApplicationError;
+
+
+/// Sample types, which can be converted into the internal i16 representation
+pub trait InputSample: Copy {
+    fn to_i16(self) -> i16;
+}
+
+impl InputSample for i16 {
+    fn to_i16(self) -> i16 {
+        self
+    }
+}
+
+impl InputSample for u16 {
+    /// The value 32768 corresponds to 0
+    fn to_i16(self) -> i16 {
+        (self as i32 - 32768) as i16
+    }
+}
+
+impl InputSample for i32 {
+    /// Only the 16 most significant bits are kept
+    fn to_i16(self) -> i16 {
+        (self >> 16) as i16
+    }
+}
+
+impl InputSample for f32 {
+    /// The boundaries are (-1.0, 1.0)
+    fn to_i16(self) -> i16 {
+        (self.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+    }
+}
+
+
+/// Defines how the channels of a source are combined before the framing
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChannelStrategy {
+    /// Only use the channel with the given index
+    Channel(usize),
+    /// Average all channels to one mono channel
+    #[default]
+    Average,
+    /// Keep the first two channels. Mono sources are played on both channels
+    Stereo
+}
+
+impl ChannelStrategy {
+
+    /// Amount of channels after the mixing
+    pub fn output_channels(&self) -> usize {
+        match self {
+            ChannelStrategy::Stereo => 2,
+            _ => 1
+        }
+    }
+
+}
+
+
+/// Converts the interleaved samples of a source with the channel strategy
+pub(crate) struct ChannelMixer {
+    strategy: ChannelStrategy,
+    channels: usize
+}
+
+impl ChannelMixer {
+
+    /// Create a mixer for a source with the given amount of channels.
+    /// Fails if the source doesn't contain the selected channel.
+    pub(crate) fn new(strategy: ChannelStrategy, channels: u16) -> Result<Self> {
+        let channels = channels as usize;
+
+        if channels == 0 {
+            Err(ApplicationError::UnsupportedFormat(String::from("0 channels")))?
+        }
+        if let ChannelStrategy::Channel(channel) = strategy {
+            if channel >= channels {
+                Err(ApplicationError::InvalidInputConfig(format!(
+                    "The channel {} is not available, the source only has {} channels", channel, channels
+                )))?
+            }
+        }
+
+        Ok(ChannelMixer { strategy, channels })
+    }
+
+    /// Amount of interleaved channels of the source
+    pub(crate) fn channels(&self) -> usize {
+        self.channels
+    }
+
+    /// Convert the interleaved samples of the source into the interleaved output channels.
+    /// Incomplete frames at the end are ignored.
+    pub(crate) fn mix<T: InputSample>(&self, data: &[T], output: &mut Vec<i16>) {
+        output.clear();
+
+        for frame in data.chunks_exact(self.channels) {
+            match self.strategy {
+                ChannelStrategy::Channel(channel) => output.push(frame[channel].to_i16()),
+                ChannelStrategy::Average => {
+                    let sum: i32 = frame.iter().map(|sample| sample.to_i16() as i32).sum();
+                    output.push((sum / self.channels as i32) as i16);
+                }
+                ChannelStrategy::Stereo => {
+                    let left = frame[0].to_i16();
+                    let right = frame.get(1).map_or(left, |sample| sample.to_i16());
+                    output.push(left);
+                    output.push(right);
+                }
+            }
+        }
+    }
+
+}
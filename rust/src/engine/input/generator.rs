@@ -0,0 +1,278 @@
+use std::f64::consts::PI;
+use std::time::Duration;
+
+use anyhow::Result;
+
+use crate::engine::errors::ApplicationError;
+use super::{DataCallback, DeviceInfo, ErrorCallback, InputConfig, InputSource};
+use super::format::ChannelMixer;
+use super::playback::{Playback, PlaybackStream};
+
+
+/// Signals which can be generated by the *GeneratorInputSource*
+#[derive(Copy, Clone, Debug)]
+pub enum Signal {
+    /// Sine wave with a fixed frequency in Hz
+    Sine { frequency: f32 },
+    /// Linear sweep from the start to the end frequency, which repeats after the duration
+    Chirp { start: f32, end: f32, duration: Duration },
+    /// Noise with equal power over all frequencies
+    WhiteNoise,
+    /// Noise with equal power per octave
+    PinkNoise,
+    /// Single full scale samples with a fixed frequency in Hz
+    ImpulseTrain { frequency: f32 },
+    /// Short clicks at the given beats per minute
+    ClickTrack { bpm: f32 },
+    /// Only zeros
+    Silence
+}
+
+
+/// Input source which generates a synthetic signal instead of listening to an audio device.
+/// Useful to test effects, filters and the sender without any audio hardware.
+pub struct GeneratorInputSource {
+    /// Generated signal
+    signal: Signal,
+
+    /// Sample rate of the generated signal
+    sample_rate: u32,
+
+    /// Amplitude of the signal between 0 and 1
+    amplitude: f32,
+
+    /// Length of the signal. Without a duration the signal will be generated endless
+    duration: Option<Duration>,
+
+    /// Real time or as fast as possible
+    playback: Playback,
+
+    /// Own playback stream
+    stream: Option<PlaybackStream>
+}
+
+
+impl GeneratorInputSource {
+
+    /// Build a new endless generator with full amplitude
+    pub fn new(signal: Signal, sample_rate: u32, playback: Playback) -> Self {
+        GeneratorInputSource {
+            signal,
+            sample_rate,
+            amplitude: 1.0,
+            duration: None,
+            playback,
+            stream: None
+        }
+    }
+
+    /// Set the amplitude between 0 and 1. Takes effect with the next stream.
+    pub fn set_amplitude(&mut self, amplitude: f32) {
+        self.amplitude = amplitude.clamp(0.0, 1.0)
+    }
+
+    /// Limit the length of the signal. Takes effect with the next stream.
+    pub fn set_duration(&mut self, duration: Option<Duration>) {
+        self.duration = duration
+    }
+
+    /// Block until the signal reached its duration.
+    /// Has to be called after the stream was started, otherwise it won't return.
+    pub fn wait(&mut self) -> Result<()> {
+        match &mut self.stream {
+            None => Err(ApplicationError::NoInputStream)?,
+            Some(stream) => {
+                stream.wait();
+                Ok(())
+            }
+        }
+    }
+
+}
+
+
+impl InputSource for GeneratorInputSource {
+
+    /// The generator is the only available device
+    fn available_devices(&self) -> Result<Vec<DeviceInfo>> {
+        Ok(vec![
+            DeviceInfo {
+                position: 0,
+                name: self.current_device_name()?,
+                channels: 1,
+                sample_rate: self.sample_rate,
+                standard: true
+            }
+        ])
+    }
+
+    fn set_device(&mut self, position: usize) -> Result<()> {
+        if position != 0 {
+            Err(ApplicationError::DeviceNotFound(format!("at position {}", position)))?
+        }
+        Ok(())
+    }
+
+    fn current_device_name(&self) -> Result<String> {
+        Ok(format!("Generator: {:?}", self.signal))
+    }
+
+    fn sample_rate(&self) -> Result<u32> {
+        Ok(self.sample_rate)
+    }
+
+    fn build_stream(&mut self, config: &InputConfig, callback: DataCallback, _error_callback: ErrorCallback) -> Result<()> {
+        // Drop the old stream before the new one starts
+        self.stream = None;
+
+        let mut oscillator = Oscillator::new(self.signal, self.sample_rate, self.amplitude);
+        let mut remaining = match self.duration {
+            None => u64::MAX,
+            Some(duration) => (duration.as_secs_f64() * self.sample_rate as f64) as u64
+        };
+
+        self.stream = Some(PlaybackStream::new(
+            self.buffer_info(config)?,
+            self.sample_rate,
+            self.playback,
+            ChannelMixer::new(config.channels, 1)?,
+            move |chunk: &mut [i16]| {
+                let length = (chunk.len() as u64).min(remaining) as usize;
+                for sample in chunk[..length].iter_mut() {
+                    *sample = oscillator.next_sample();
+                }
+
+                remaining -= length as u64;
+                length
+            },
+            callback
+        ));
+
+        Ok(())
+    }
+
+    fn start_stream(&self) -> Result<()> {
+        match &self.stream {
+            None => Err(ApplicationError::NoInputStream)?,
+            Some(stream) => {
+                stream.play();
+                Ok(())
+            }
+        }
+    }
+
+    fn pause_stream(&self) -> Result<()> {
+        match &self.stream {
+            None => Err(ApplicationError::NoInputStream)?,
+            Some(stream) => {
+                stream.pause();
+                Ok(())
+            }
+        }
+    }
+
+}
+
+
+/// Calculates the signal sample by sample
+struct Oscillator {
+    signal: Signal,
+    sample_rate: f64,
+    amplitude: f64,
+
+    /// Index of the next sample
+    index: u64,
+    /// Current phase of the sine and chirp signal
+    phase: f64,
+    /// State of the xorshift noise generator
+    seed: u32,
+    /// Filter state of the pink noise
+    pink: [f64; 3]
+}
+
+impl Oscillator {
+    /// Length of a single click of the click track in seconds
+    const CLICK_LENGTH: f64 = 0.005;
+    /// Frequency of the click
+    const CLICK_FREQUENCY: f64 = 2000.0;
+
+    fn new(signal: Signal, sample_rate: u32, amplitude: f32) -> Self {
+        Oscillator {
+            signal,
+            sample_rate: sample_rate as f64,
+            amplitude: amplitude as f64,
+            index: 0,
+            phase: 0.0,
+            seed: 0x9E37_79B9,
+            pink: [0.0; 3]
+        }
+    }
+
+    /// Calculate the next sample
+    fn next_sample(&mut self) -> i16 {
+        let time = self.index as f64 / self.sample_rate;
+
+        let value = match self.signal {
+            Signal::Sine { frequency } => {
+                self.advance_phase(frequency as f64)
+            }
+            Signal::Chirp { start, end, duration } => {
+                // Position inside the current sweep between 0 and 1
+                let length = duration.as_secs_f64().max(1.0 / self.sample_rate);
+                let position = (time % length) / length;
+                let frequency = start as f64 + (end - start) as f64 * position;
+
+                self.advance_phase(frequency)
+            }
+            Signal::WhiteNoise => self.white_noise(),
+            Signal::PinkNoise => {
+                // Paul Kellet's economy filter
+                let white = self.white_noise();
+                self.pink[0] = 0.99765 * self.pink[0] + white * 0.0990460;
+                self.pink[1] = 0.96300 * self.pink[1] + white * 0.2965164;
+                self.pink[2] = 0.57000 * self.pink[2] + white * 1.0526913;
+
+                (self.pink[0] + self.pink[1] + self.pink[2] + white * 0.1848) * 0.25
+            }
+            Signal::ImpulseTrain { frequency } => {
+                let period = (self.sample_rate / frequency as f64).round().max(1.0) as u64;
+                match self.index % period {
+                    0 => 1.0,
+                    _ => 0.0
+                }
+            }
+            Signal::ClickTrack { bpm } => {
+                let period = (self.sample_rate * 60.0 / bpm as f64).round().max(1.0) as u64;
+                let click_time = (self.index % period) as f64 / self.sample_rate;
+
+                if click_time < Self::CLICK_LENGTH {
+                    // Decaying burst
+                    let envelope = 1.0 - click_time / Self::CLICK_LENGTH;
+                    envelope * (2.0 * PI * Self::CLICK_FREQUENCY * click_time).sin()
+                } else {
+                    0.0
+                }
+            }
+            Signal::Silence => 0.0
+        };
+
+        self.index += 1;
+        (value.clamp(-1.0, 1.0) * self.amplitude * i16::MAX as f64) as i16
+    }
+
+    /// Calculate the sine of the current phase and increase the phase for the frequency
+    fn advance_phase(&mut self, frequency: f64) -> f64 {
+        let value = self.phase.sin();
+        self.phase = (self.phase + 2.0 * PI * frequency / self.sample_rate) % (2.0 * PI);
+        value
+    }
+
+    /// Random value between -1 and 1
+    fn white_noise(&mut self) -> f64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 17;
+        self.seed ^= self.seed << 5;
+
+        self.seed as f64 / u32::MAX as f64 * 2.0 - 1.0
+    }
+}
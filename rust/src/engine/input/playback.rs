@@ -0,0 +1,150 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::engine::utils::{AudioBuffer, BufferInfo};
+use super::DataCallback;
+use super::format::ChannelMixer;
+use super::resample::Resampler;
+
+
+/// Defines how fast the samples of a source will be handed to the callback
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Playback {
+    /// The frames are delivered at the sample rate of the source, like a real audio device would do
+    RealTime,
+    /// Every frame is delivered as fast as possible. Useful for offline rendering and tests
+    Unpaced
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum PlaybackState {
+    Paused,
+    Playing,
+    Stopped
+}
+
+/// State which is shared between the source and the playback thread
+struct Control {
+    state: Mutex<PlaybackState>,
+    changed: Condvar
+}
+
+
+/// Runs a sample producer in its own thread and buffers the samples
+/// the same way as the device input stream does.
+/// The producer writes the next interleaved samples into the given slice and returns how many samples were written.
+/// If no sample was written, the end of the source is reached and the thread stops.
+pub(crate) struct PlaybackStream {
+    control: Arc<Control>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl PlaybackStream {
+
+    /// Build a new paused playback stream.
+    /// The samples of the producer have the given sample rate and are resampled to the sample rate of the info.
+    pub(crate) fn new<P>(
+        info: BufferInfo,
+        sample_rate: u32,
+        playback: Playback,
+        mixer: ChannelMixer,
+        mut producer: P,
+        mut callback: DataCallback
+    ) -> Self
+        where
+            P: FnMut(&mut [i16]) -> usize + Send + 'static
+    {
+        let control = Arc::new(Control {
+            state: Mutex::new(PlaybackState::Paused),
+            changed: Condvar::new()
+        });
+
+        let thread_control = control.clone();
+        let handle = std::thread::spawn(move || {
+            // One chunk of samples equals one hop of the window
+            let chunk_length = (info.hop_length as u64 * sample_rate as u64 / info.sample_rate as u64).max(1) as usize;
+            let mut chunk = vec![0; chunk_length * mixer.channels()];
+            let chunk_duration = Duration::from_secs_f64(chunk_length as f64 / sample_rate as f64);
+
+            let mut resampler = Resampler::new(sample_rate, info.sample_rate, info.channels);
+            let mut mixed = vec![];
+            let mut resampled = vec![];
+            let mut buffer = AudioBuffer::from_info(info);
+
+            // Start of the current playing period and the amount of chunks sent since then
+            let mut start = Instant::now();
+            let mut chunks: u32 = 0;
+
+            loop {
+                // Wait as long as the stream is paused
+                let mut state = thread_control.state.lock().unwrap();
+                if *state == PlaybackState::Paused {
+                    while *state == PlaybackState::Paused {
+                        state = thread_control.changed.wait(state).unwrap();
+                    }
+                    start = Instant::now();
+                    chunks = 0;
+                }
+                if *state == PlaybackState::Stopped { break }
+                drop(state);
+
+                let length = producer(chunk.as_mut_slice());
+                if length == 0 { break }
+
+                mixer.mix(&chunk[..length], &mut mixed);
+                resampler.process(&mixed, &mut resampled);
+                buffer.push(resampled.iter().cloned(), |frame| callback(frame));
+
+                // Sleep until the next chunk would be captured
+                if playback == Playback::RealTime {
+                    chunks += 1;
+                    let next = start + chunk_duration * chunks;
+                    let now = Instant::now();
+                    if next > now {
+                        std::thread::sleep(next - now);
+                    }
+                }
+            }
+        });
+
+        PlaybackStream {
+            control,
+            handle: Some(handle)
+        }
+    }
+
+    /// Start or continue the playback
+    pub(crate) fn play(&self) {
+        self.set_state(PlaybackState::Playing)
+    }
+
+    /// Pause the playback
+    pub(crate) fn pause(&self) {
+        self.set_state(PlaybackState::Paused)
+    }
+
+    /// Block until the producer reached its end
+    pub(crate) fn wait(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            // A panic inside the callback is already reported by the thread itself
+            let _ = handle.join();
+        }
+    }
+
+    fn set_state(&self, value: PlaybackState) {
+        let mut state = self.control.state.lock().unwrap();
+        if *state != PlaybackState::Stopped {
+            *state = value;
+        }
+        self.control.changed.notify_all();
+    }
+
+}
+
+impl Drop for PlaybackStream {
+    fn drop(&mut self) {
+        self.set_state(PlaybackState::Stopped);
+        self.wait();
+    }
+}
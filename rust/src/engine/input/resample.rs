@@ -0,0 +1,136 @@
+use std::f32::consts::PI;
+
+
+/// Second order Butterworth low pass for interleaved samples.
+/// Every channel keeps its own state, so the signal is continuous between the calls.
+struct LowPass {
+    b: [f32; 3],
+    a: [f32; 2],
+    /// Last two inputs and outputs of every channel
+    state: Vec<[f32; 4]>
+}
+
+impl LowPass {
+
+    /// Cutoff relative to the sample rate, e.g. 0.25 is a quarter of the sample rate
+    fn new(cutoff: f32, channels: usize) -> Self {
+        let omega = 2.0 * PI * cutoff;
+        let alpha = omega.sin() / std::f32::consts::SQRT_2;
+        let cos = omega.cos();
+        let a0 = 1.0 + alpha;
+
+        LowPass {
+            b: [(1.0 - cos) / 2.0 / a0, (1.0 - cos) / a0, (1.0 - cos) / 2.0 / a0],
+            a: [-2.0 * cos / a0, (1.0 - alpha) / a0],
+            state: vec![[0.0; 4]; channels]
+        }
+    }
+
+    fn process(&mut self, samples: &mut [f32]) {
+        let channels = self.state.len();
+
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let [x1, x2, y1, y2] = &mut self.state[i % channels];
+            let x = *sample;
+            let y = self.b[0] * x + self.b[1] * *x1 + self.b[2] * *x2 - self.a[0] * *y1 - self.a[1] * *y2;
+
+            (*x2, *x1, *y2, *y1) = (*x1, x, *y1, y);
+            *sample = y;
+        }
+    }
+}
+
+
+/// Converts interleaved samples from the sample rate of the source to the internal sample rate.
+/// The samples are linear interpolated, which is precise enough for the visualization.
+/// Before the samples are decimated, a low pass removes the frequencies above the new nyquist frequency.
+/// The last frame of the previous call is kept, so the signal is continuous between the calls.
+pub(crate) struct Resampler {
+    /// Amount of input samples between two output samples
+    step: f64,
+    channels: usize,
+
+    /// Anti aliasing filters, which are only used for a lower sample rate
+    filters: Vec<LowPass>,
+    filtered: Vec<f32>,
+
+    /// Position of the next output sample.
+    /// The position 0 is the last frame of the previous call, 1 is the first new frame
+    position: f64,
+    previous: Vec<f32>
+}
+
+impl Resampler {
+
+    /// Cutoff of the anti aliasing filter relative to the new sample rate, a bit below the nyquist frequency
+    const CUTOFF: f32 = 0.45;
+    /// Amount of cascaded low pass filters
+    const FILTER_ORDER: usize = 2;
+
+    pub(crate) fn new(from: u32, to: u32, channels: usize) -> Self {
+        let step = from as f64 / to as f64;
+        let filters = match step > 1.0 {
+            true => (0..Self::FILTER_ORDER)
+                .map(|_| LowPass::new(Self::CUTOFF / step as f32, channels.max(1)))
+                .collect(),
+            false => vec![]
+        };
+
+        Resampler {
+            step,
+            channels,
+            filters,
+            filtered: vec![],
+            position: 1.0,
+            previous: vec![0.0; channels]
+        }
+    }
+
+    /// Check if the sample rates are different
+    pub(crate) fn is_active(&self) -> bool {
+        self.step != 1.0
+    }
+
+    /// Resample the interleaved samples into the output
+    pub(crate) fn process(&mut self, input: &[i16], output: &mut Vec<i16>) {
+        output.clear();
+
+        if !self.is_active() {
+            output.extend_from_slice(input);
+            return
+        }
+
+        let frames = input.len() / self.channels;
+        if frames == 0 { return }
+
+        self.filtered.clear();
+        self.filtered.extend(input[..frames * self.channels].iter().map(|&sample| sample as f32));
+        for filter in self.filters.iter_mut() {
+            filter.process(&mut self.filtered);
+        }
+        let input = &self.filtered;
+
+        // Frame at the position, where 0 is the previous frame
+        let frame = |index: usize, channel: usize| match index {
+            0 => self.previous[channel],
+            _ => input[(index - 1) * self.channels + channel]
+        };
+
+        while self.position < frames as f64 {
+            let index = self.position.floor() as usize;
+            let fraction = (self.position - index as f64) as f32;
+
+            for channel in 0..self.channels {
+                let left = frame(index, channel);
+                let right = frame(index + 1, channel);
+                output.push((left + (right - left) * fraction).clamp(i16::MIN as f32, i16::MAX as f32) as i16);
+            }
+
+            self.position += self.step;
+        }
+
+        self.position -= frames as f64;
+        self.previous.copy_from_slice(&input[(frames - 1) * self.channels..frames * self.channels]);
+    }
+
+}
@@ -0,0 +1,54 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+
+/// Remembers the name of the selected input device in a file,
+/// so the same device can be selected again after a restart.
+pub struct DeviceStore {
+    path: PathBuf
+}
+
+impl DeviceStore {
+
+    /// Create a store, which uses the file at the given path
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        DeviceStore {
+            path: path.as_ref().to_path_buf()
+        }
+    }
+
+    /// Path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the name of the last selected device.
+    /// Returns None if no device was stored yet.
+    pub fn load(&self) -> Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => {
+                let name = content.trim();
+                match name.is_empty() {
+                    true => Ok(None),
+                    false => Ok(Some(name.to_string()))
+                }
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)?
+        }
+    }
+
+    /// Store the name of the selected device
+    pub fn save(&self, name: &str) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, name)?;
+
+        Ok(())
+    }
+
+}
@@ -0,0 +1,134 @@
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use anyhow::Result;
+use log::warn;
+
+use crate::engine::events::{DeviceEvent, EventBus};
+use super::{DeviceInfo, DeviceInputSource, InputSource};
+
+
+/// State which is shared between the watcher and its thread
+struct Shared {
+    devices: Vec<DeviceInfo>,
+    events: EventBus<DeviceEvent>
+}
+
+/// Polls the available input devices in its own thread and reports every change.
+/// The thread stops when the watcher is dropped.
+pub struct DeviceWatcher {
+    shared: Arc<Mutex<Shared>>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<()>>
+}
+
+impl DeviceWatcher {
+    /// Default time between two polls
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Watch the input devices of the pc's default audio host
+    pub fn new(interval: Duration) -> Self {
+        // The host is created inside the thread for every poll, because it can't be sent on every platform
+        Self::with_lister(interval, || DeviceInputSource::new().available_devices())
+    }
+
+    /// Watch the devices, which are returned by the lister.
+    /// The lister is called once directly and then once per interval.
+    pub fn with_lister<L>(interval: Duration, mut lister: L) -> Self
+        where
+            L: FnMut() -> Result<Vec<DeviceInfo>> + Send + 'static
+    {
+        let shared = Arc::new(Mutex::new(Shared {
+            devices: vec![],
+            events: EventBus::new()
+        }));
+        let (stop, stopped) = channel();
+
+        // The first poll happens directly, so the devices are known after the creation
+        Self::watch(&shared, &mut lister, true);
+
+        let thread_shared = shared.clone();
+        let handle = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                Self::watch(&thread_shared, &mut lister, false);
+            }
+        });
+
+        DeviceWatcher {
+            shared,
+            stop: Some(stop),
+            handle: Some(handle)
+        }
+    }
+
+    /// Receive all following device changes
+    pub fn subscribe(&self) -> Receiver<DeviceEvent> {
+        self.shared.lock().unwrap().events.subscribe()
+    }
+
+    /// Devices of the last poll
+    pub fn devices(&self) -> Vec<DeviceInfo> {
+        self.shared.lock().unwrap().devices.clone()
+    }
+
+    /// Poll the devices and emit the changes since the last poll.
+    /// The first poll only stores the devices.
+    fn watch<L>(shared: &Mutex<Shared>, lister: &mut L, first: bool)
+        where
+            L: FnMut() -> Result<Vec<DeviceInfo>>
+    {
+        let devices = match lister() {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("The input devices can't be listed: {}", err);
+                return
+            }
+        };
+
+        let mut shared = shared.lock().unwrap();
+        if !first {
+            for event in diff_devices(&shared.devices, &devices) {
+                shared.events.emit(event);
+            }
+        }
+        shared.devices = devices;
+    }
+
+}
+
+impl Drop for DeviceWatcher {
+    fn drop(&mut self) {
+        // Dropping the sender wakes up the thread
+        self.stop = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+
+/// Compare two device lists by the names of the devices.
+/// The positions are ignored, because they change when other devices are added or removed.
+pub fn diff_devices(old: &[DeviceInfo], new: &[DeviceInfo]) -> Vec<DeviceEvent> {
+    let mut events = vec![];
+
+    // Devices with the same name are matched once, so two equal interfaces are counted correctly
+    let mut remaining: Vec<&DeviceInfo> = old.iter().collect();
+    for device in new.iter() {
+        match remaining.iter().position(|old| old.name == device.name) {
+            Some(index) => { remaining.remove(index); }
+            None => events.push(DeviceEvent::Added(device.clone()))
+        }
+    }
+    events.extend(remaining.into_iter().map(|device| DeviceEvent::Removed(device.clone())));
+
+    let old_default = old.iter().find(|device| device.standard);
+    let new_default = new.iter().find(|device| device.standard);
+    if old_default.map(|device| &device.name) != new_default.map(|device| &device.name) {
+        events.push(DeviceEvent::DefaultChanged(new_default.cloned()));
+    }
+
+    events
+}
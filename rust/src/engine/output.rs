@@ -0,0 +1,188 @@
+use anyhow::Result;
+
+use super::errors::ApplicationError;
+use super::sender::Sender;
+
+pub mod correction;
+pub mod dither;
+pub mod layout;
+pub mod palette;
+pub mod pixel;
+
+use correction::{ColorCorrection, Gamma, PowerBudget};
+use dither::TemporalDither;
+use layout::LedLayout;
+use pixel::{PixelFormat, PixelFrame};
+
+
+/// Destination of the processed frames, e.g. the leds behind a *Sender*.
+/// The engine starts the sink with every new stream and stops it, when the stream is paused.
+pub trait OutputSink: Send {
+
+    /// Prepare the sink for the frames of a new stream with the given frame rate
+    fn start(&mut self, _frame_rate: u32) -> Result<()> {
+        Ok(())
+    }
+
+    /// Send the channel data of one frame
+    fn write(&mut self, frame: &[u8]) -> Result<()>;
+
+    /// The stream was paused, so no frames will follow for now
+    fn stop(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+
+impl OutputSink for Sender {
+
+    /// The sender is paced to the frame rate of the stream
+    fn start(&mut self, frame_rate: u32) -> Result<()> {
+        self.set_frame_rate(frame_rate)
+    }
+
+    fn write(&mut self, frame: &[u8]) -> Result<()> {
+        self.send(frame)
+    }
+
+    /// Turn all leds off without waiting for the next frame
+    fn stop(&mut self) -> Result<()> {
+        self.blackout()
+    }
+}
+
+
+/// Sink which hands every frame to a function. Useful for custom outputs and tests
+pub struct CallbackSink<F>
+    where
+        F: FnMut(&[u8]) + Send
+{
+    callback: F,
+    on_stop: Option<Box<dyn FnMut() + Send>>
+}
+
+impl<F> CallbackSink<F>
+    where
+        F: FnMut(&[u8]) + Send
+{
+    pub fn new(callback: F) -> Self {
+        CallbackSink { callback, on_stop: None }
+    }
+
+    /// Call the function when the stream of the sink ends
+    pub fn with_stop(mut self, on_stop: impl FnMut() + Send + 'static) -> Self {
+        self.on_stop = Some(Box::new(on_stop));
+        self
+    }
+}
+
+impl<F> OutputSink for CallbackSink<F>
+    where
+        F: FnMut(&[u8]) + Send
+{
+    fn write(&mut self, frame: &[u8]) -> Result<()> {
+        (self.callback)(frame);
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        if let Some(on_stop) = self.on_stop.as_mut() {
+            on_stop();
+        }
+        Ok(())
+    }
+}
+
+
+/// Configuration of the output path, which converts the frames of the effects into the bytes of the sink
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct OutputConfig {
+    /// Channel order and channels of the leds
+    pub format: PixelFormat,
+    /// Gamma correction of every channel
+    pub gamma: Gamma,
+    /// Global brightness between 0 and 1
+    pub brightness: f32,
+    /// Optional limit of the current, which scales the whole frame down
+    pub power_budget: Option<PowerBudget>,
+    /// Temporal dithering of the 8 bit output.
+    /// Can be disabled for controllers, which do their own dithering
+    pub dithering: bool
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        OutputConfig {
+            format: PixelFormat::default(),
+            gamma: Gamma::default(),
+            brightness: 1.0,
+            power_budget: None,
+            dithering: false
+        }
+    }
+}
+
+impl OutputConfig {
+
+    /// Check if the output path can be built with the configuration
+    pub fn validate(&self) -> Result<()> {
+        if self.gamma.channels().iter().any(|gamma| !(*gamma > 0.0 && gamma.is_finite())) {
+            Err(ApplicationError::InvalidOutputConfig(format!("The gamma {:?} must be larger than 0", self.gamma)))?
+        }
+        if !(0.0..=1.0).contains(&self.brightness) {
+            Err(ApplicationError::InvalidOutputConfig(format!("The brightness {} must be between 0 and 1", self.brightness)))?
+        }
+        if let Some(budget) = &self.power_budget {
+            if !(budget.max_milliamps > 0.0 && budget.channel_milliamps > 0.0 && budget.idle_milliamps >= 0.0) {
+                Err(ApplicationError::InvalidOutputConfig(format!("The power budget {:?} is invalid", budget)))?
+            }
+        }
+
+        Ok(())
+    }
+
+}
+
+
+/// Converts the pixel frames of the worker into the bytes of the sink.
+/// Runs inside the audio thread, so it owns all buffers.
+pub struct OutputPipeline {
+    config: OutputConfig,
+    /// Without a layout the frame is sent in the order of the canvas
+    layout: Option<LedLayout>,
+    correction: ColorCorrection,
+    dither: TemporalDither,
+    frame: PixelFrame
+}
+
+impl OutputPipeline {
+
+    pub fn new(config: OutputConfig, layout: Option<LedLayout>) -> Self {
+        OutputPipeline {
+            config,
+            layout,
+            correction: ColorCorrection::new(config.gamma, config.brightness, config.power_budget),
+            dither: TemporalDither::new(),
+            frame: PixelFrame::default()
+        }
+    }
+
+    /// Arrange the frame for the physical leds, correct it and convert it into the bytes of the sink
+    pub fn process(&mut self, frame: &PixelFrame, output: &mut Vec<u8>) {
+        match &self.layout {
+            Some(layout) => layout.map(frame, &mut self.frame),
+            None => self.frame.clone_from(frame)
+        }
+        self.correction.apply(&mut self.frame);
+
+        match self.config.dithering {
+            true => {
+                let dither = &mut self.dither;
+                dither.resize(self.frame.len() * 4);
+                self.frame.quantize(self.config.format, output, |index, value| dither.quantize(index, value));
+            }
+            false => self.frame.to_bytes(self.config.format, output)
+        }
+    }
+
+}
@@ -0,0 +1,164 @@
+use super::pixel::{Color, PixelFrame};
+
+
+/// Gamma of every colour channel.
+/// Leds have a linear brightness, but the eye perceives the brightness logarithmic,
+/// so a gamma above 1 darkens the lower values.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Gamma {
+    pub red: f32,
+    pub green: f32,
+    pub blue: f32,
+    pub white: f32
+}
+
+impl Default for Gamma {
+    fn default() -> Self {
+        Gamma::LINEAR
+    }
+}
+
+impl Gamma {
+    /// No correction
+    pub const LINEAR: Gamma = Gamma::uniform(1.0);
+    /// Typical correction for WS2812 strips
+    pub const WS2812: Gamma = Gamma::uniform(2.8);
+
+    /// Same gamma for every channel
+    pub const fn uniform(gamma: f32) -> Gamma {
+        Gamma { red: gamma, green: gamma, blue: gamma, white: gamma }
+    }
+
+    /// Gammas in the order red, green, blue, white
+    pub fn channels(&self) -> [f32; 4] {
+        [self.red, self.green, self.blue, self.white]
+    }
+}
+
+
+/// Estimation of the current, which is drawn by the leds.
+/// The frame is scaled down, if the estimated current is above the limit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PowerBudget {
+    /// Maximum current of all leds in mA
+    pub max_milliamps: f32,
+    /// Current of a single channel at full brightness in mA
+    pub channel_milliamps: f32,
+    /// Current of a single led, which is switched off, in mA
+    pub idle_milliamps: f32
+}
+
+impl PowerBudget {
+    /// Budget for WS2812 leds with 20 mA per channel and 1 mA for the controller
+    pub fn new(max_milliamps: f32) -> PowerBudget {
+        PowerBudget { max_milliamps, channel_milliamps: 20.0, idle_milliamps: 1.0 }
+    }
+
+    /// Estimated current of the frame in mA
+    pub fn estimate(&self, frame: &PixelFrame) -> f32 {
+        let idle = self.idle_milliamps * frame.len() as f32;
+        idle + self.channel_milliamps * channel_sum(frame) / u16::MAX as f32
+    }
+
+    /// Factor between 0 and 1, which keeps the frame inside the budget
+    pub fn scale_factor(&self, frame: &PixelFrame) -> f32 {
+        let idle = self.idle_milliamps * frame.len() as f32;
+        let active = self.channel_milliamps * channel_sum(frame) / u16::MAX as f32;
+        let available = (self.max_milliamps - idle).max(0.0);
+
+        match active > available {
+            true => available / active,
+            false => 1.0
+        }
+    }
+}
+
+/// Sum of all channels of the frame
+fn channel_sum(frame: &PixelFrame) -> f32 {
+    frame.pixels().iter()
+        .flat_map(|pixel| pixel.channels())
+        .map(|value| value as f32)
+        .sum()
+}
+
+
+/// Lookup table of the gamma correction for one channel.
+/// The 16 bit value is interpolated between the entries.
+struct GammaTable {
+    values: Vec<u16>
+}
+
+impl GammaTable {
+    /// Amount of intervals of the table
+    const STEPS: usize = 256;
+
+    fn new(gamma: f32) -> GammaTable {
+        let values = (0..=Self::STEPS)
+            .map(|i| {
+                let value = (i as f32 / Self::STEPS as f32).powf(gamma);
+                (value * u16::MAX as f32).round() as u16
+            })
+            .collect();
+
+        GammaTable { values }
+    }
+
+    fn apply(&self, value: u16) -> u16 {
+        let scaled = value as u32 * Self::STEPS as u32;
+        let index = (scaled / u16::MAX as u32) as usize;
+        let fraction = (scaled % u16::MAX as u32) as f32 / u16::MAX as f32;
+
+        match self.values.get(index + 1) {
+            None => self.values[index],
+            Some(next) => {
+                let left = self.values[index] as f32;
+                (left + (*next as f32 - left) * fraction).round() as u16
+            }
+        }
+    }
+}
+
+
+/// Applies the gamma correction, the brightness and the power budget to the frames
+pub struct ColorCorrection {
+    /// Tables of red, green, blue and white. Without any table the gamma is linear
+    tables: Option<[GammaTable; 4]>,
+    brightness: f32,
+    budget: Option<PowerBudget>
+}
+
+impl ColorCorrection {
+
+    pub fn new(gamma: Gamma, brightness: f32, budget: Option<PowerBudget>) -> ColorCorrection {
+        let tables = match gamma == Gamma::LINEAR {
+            true => None,
+            false => Some(gamma.channels().map(GammaTable::new))
+        };
+
+        ColorCorrection { tables, brightness: brightness.clamp(0.0, 1.0), budget }
+    }
+
+    /// Correct every pixel of the frame
+    pub fn apply(&self, frame: &mut PixelFrame) {
+        for pixel in frame.pixels_mut() {
+            if let Some(tables) = &self.tables {
+                let mut channels = pixel.channels();
+                for (value, table) in channels.iter_mut().zip(tables.iter()) {
+                    *value = table.apply(*value);
+                }
+                *pixel = Color::from_channels(channels);
+            }
+            *pixel = pixel.scale(self.brightness);
+        }
+
+        // The current depends on the corrected values
+        if let Some(budget) = &self.budget {
+            let factor = budget.scale_factor(frame);
+            if factor < 1.0 {
+                for pixel in frame.pixels_mut() {
+                    *pixel = pixel.scale(factor);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use super::pixel::to_byte;
+
+
+/// Temporal dithering of the 16 bit channels into 8 bit.
+/// The rounding error of every channel is carried into the next frame,
+/// so the average of successive frames matches the precise value.
+/// Values between two 8 bit steps alternate instead of visibly jumping at low brightness.
+pub struct TemporalDither {
+    /// Remaining error of every channel in 16 bit steps
+    errors: Vec<i32>
+}
+
+impl Default for TemporalDither {
+    fn default() -> Self {
+        TemporalDither::new()
+    }
+}
+
+impl TemporalDither {
+
+    pub fn new() -> TemporalDither {
+        TemporalDither { errors: vec![] }
+    }
+
+    /// Prepare the errors for frames with the amount of channels.
+    /// The errors are reset, if the length changed.
+    pub fn resize(&mut self, channels: usize) {
+        if self.errors.len() != channels {
+            self.errors.clear();
+            self.errors.resize(channels, 0);
+        }
+    }
+
+    /// Forget the errors of the previous frames
+    pub fn reset(&mut self) {
+        self.errors.fill(0)
+    }
+
+    /// Quantize the channel with the given index and keep its rounding error
+    pub fn quantize(&mut self, index: usize, value: u16) -> u8 {
+        let error = match self.errors.get_mut(index) {
+            Some(error) => error,
+            None => return to_byte(value)
+        };
+
+        let target = value as i32 + *error;
+        let byte = ((target + 128) / 257).clamp(0, u8::MAX as i32);
+        *error = target - byte * 257;
+
+        byte as u8
+    }
+}
@@ -0,0 +1,187 @@
+use anyhow::Result;
+
+use crate::engine::errors::ApplicationError;
+use super::pixel::{Color, PixelFrame};
+
+
+/// Order in which the leds of a matrix are wired
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Wiring {
+    /// Every row starts on the left side
+    #[default]
+    Rows,
+    /// Every second row runs from the right to the left, also known as zigzag
+    SerpentineRows,
+    /// Every column starts at the top
+    Columns,
+    /// Every second column runs from the bottom to the top
+    SerpentineColumns
+}
+
+
+/// Part of the physical installation. The segments are wired one after another.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Segment {
+    /// Consecutive pixels of the canvas in row order, beginning at the index *start*.
+    /// A reversed strip begins with the last pixel.
+    Strip { start: usize, length: usize, reversed: bool },
+    /// Physical leds, which don't belong to the canvas and stay dark
+    Gap { length: usize },
+    /// Rectangle of the canvas with its top left corner at x and y
+    Matrix { x: usize, y: usize, width: usize, height: usize, wiring: Wiring }
+}
+
+impl Segment {
+
+    /// Amount of physical leds of the segment
+    pub fn length(&self) -> usize {
+        match self {
+            Segment::Strip { length, .. } => *length,
+            Segment::Gap { length } => *length,
+            Segment::Matrix { width, height, .. } => width * height
+        }
+    }
+
+}
+
+
+/// Maps the logical canvas of the effects onto the physical leds.
+/// The canvas has a width and a height, a simple strip is a canvas with the height 1.
+/// Pixels of the canvas can be shown on multiple leds or on none at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LedLayout {
+    width: usize,
+    height: usize,
+    segments: Vec<Segment>,
+
+    /// Canvas index of every physical led
+    table: Vec<Option<usize>>
+}
+
+impl LedLayout {
+
+    /// Create a layout for a canvas with the segments.
+    /// Fails if a segment leaves the canvas.
+    pub fn new(width: usize, height: usize, segments: Vec<Segment>) -> Result<LedLayout> {
+        if width == 0 || height == 0 {
+            Err(ApplicationError::InvalidLayout(format!("The canvas {}x{} is empty", width, height)))?
+        }
+
+        let pixels = width.checked_mul(height).ok_or_else(|| ApplicationError::InvalidLayout(
+            format!("The canvas {}x{} is too large", width, height)
+        ))?;
+
+        let mut table = vec![];
+        for segment in segments.iter() {
+            match *segment {
+                Segment::Strip { start, length, reversed } => {
+                    if leaves(start, length, pixels) {
+                        Err(ApplicationError::InvalidLayout(format!(
+                            "The strip from {} with {} leds leaves the canvas with {} pixels", start, length, pixels
+                        )))?
+                    }
+                    match reversed {
+                        true => table.extend((start..start + length).rev().map(Some)),
+                        false => table.extend((start..start + length).map(Some))
+                    }
+                }
+                Segment::Gap { length } => table.resize(table.len() + length, None),
+                Segment::Matrix { x, y, width: columns, height: rows, wiring } => {
+                    if leaves(x, columns, width) || leaves(y, rows, height) {
+                        Err(ApplicationError::InvalidLayout(format!(
+                            "The matrix {}x{} at ({}, {}) leaves the canvas {}x{}", columns, rows, x, y, width, height
+                        )))?
+                    }
+                    for i in 0..columns * rows {
+                        let (column, row) = matrix_position(i, columns, rows, wiring);
+                        table.push(Some((y + row) * width + x + column));
+                    }
+                }
+            }
+        }
+
+        Ok(LedLayout { width, height, segments, table })
+    }
+
+    /// Single strip, which shows the canvas in its order
+    pub fn strip(length: usize) -> Result<LedLayout> {
+        LedLayout::new(length, 1, vec![Segment::Strip { start: 0, length, reversed: false }])
+    }
+
+    /// Single matrix, which covers the whole canvas
+    pub fn matrix(width: usize, height: usize, wiring: Wiring) -> Result<LedLayout> {
+        LedLayout::new(width, height, vec![Segment::Matrix { x: 0, y: 0, width, height, wiring }])
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn segments(&self) -> &[Segment] {
+        self.segments.as_slice()
+    }
+
+    /// Amount of pixels of the canvas
+    pub fn canvas_length(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Amount of physical leds including the gaps
+    pub fn physical_length(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Canvas index, which is shown by the physical led
+    pub fn canvas_index(&self, physical: usize) -> Option<usize> {
+        self.table.get(physical).copied().flatten()
+    }
+
+    /// Arrange the pixels of the canvas in the order of the physical leds.
+    /// Gaps and pixels outside of the canvas are black.
+    pub fn map(&self, canvas: &PixelFrame, physical: &mut PixelFrame) {
+        physical.resize(self.table.len());
+
+        for (pixel, index) in physical.pixels_mut().iter_mut().zip(self.table.iter()) {
+            *pixel = index
+                .and_then(|index| canvas.pixels().get(index).copied())
+                .unwrap_or(Color::BLACK);
+        }
+    }
+
+}
+
+
+/// Whether the range from start with the length ends behind the limit or overflows
+fn leaves(start: usize, length: usize, limit: usize) -> bool {
+    match start.checked_add(length) {
+        Some(end) => end > limit,
+        None => true
+    }
+}
+
+
+/// Column and row of the i-th led of a matrix
+fn matrix_position(i: usize, width: usize, height: usize, wiring: Wiring) -> (usize, usize) {
+    match wiring {
+        Wiring::Rows => (i % width, i / width),
+        Wiring::SerpentineRows => {
+            let row = i / width;
+            match row % 2 {
+                0 => (i % width, row),
+                _ => (width - 1 - i % width, row)
+            }
+        }
+        Wiring::Columns => (i / height, i % height),
+        Wiring::SerpentineColumns => {
+            let column = i / height;
+            match column % 2 {
+                0 => (column, i % height),
+                _ => (column, height - 1 - i % height)
+            }
+        }
+    }
+}
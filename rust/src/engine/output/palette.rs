@@ -0,0 +1,255 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::Result;
+
+use crate::engine::errors::ApplicationError;
+use super::pixel::Color;
+
+
+/// Colour at a position of a gradient
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColorStop {
+    /// Position between 0 and 1
+    pub position: f32,
+    pub color: Color
+}
+
+impl ColorStop {
+    pub const fn new(position: f32, color: Color) -> ColorStop {
+        ColorStop { position, color }
+    }
+}
+
+
+/// Gradient with linear interpolation between its stops.
+/// Before the first and after the last stop the colour of the stop is kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Vec<ColorStop>
+}
+
+impl Gradient {
+
+    /// Create a gradient of the stops, which are sorted by their position.
+    /// Fails without any stop or if a position is outside of 0 and 1.
+    pub fn new(mut stops: Vec<ColorStop>) -> Result<Gradient> {
+        if stops.is_empty() {
+            Err(ApplicationError::InvalidPalette(String::from("A gradient needs at least one stop")))?
+        }
+        if let Some(stop) = stops.iter().find(|stop| !(0.0..=1.0).contains(&stop.position)) {
+            Err(ApplicationError::InvalidPalette(format!(
+                "The position {} is outside of 0 and 1", stop.position
+            )))?
+        }
+
+        stops.sort_by(|a, b| a.position.total_cmp(&b.position));
+        Ok(Gradient { stops })
+    }
+
+    /// Gradient with equally spaced colours
+    pub fn even(colors: &[Color]) -> Result<Gradient> {
+        let steps = (colors.len().max(2) - 1) as f32;
+        let stops = colors.iter().enumerate()
+            .map(|(i, color)| ColorStop::new(i as f32 / steps, *color))
+            .collect();
+
+        Gradient::new(stops)
+    }
+
+    pub fn stops(&self) -> &[ColorStop] {
+        self.stops.as_slice()
+    }
+
+    /// Colour at the position between 0 and 1
+    pub fn color_at(&self, position: f32) -> Color {
+        let next = self.stops.iter().position(|stop| stop.position > position);
+
+        match next {
+            None => self.stops[self.stops.len() - 1].color,
+            Some(0) => self.stops[0].color,
+            Some(index) => {
+                let left = &self.stops[index - 1];
+                let right = &self.stops[index];
+                let factor = (position - left.position) / (right.position - left.position);
+
+                left.color.mix(&right.color, factor)
+            }
+        }
+    }
+}
+
+
+/// Predefined gradients
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NamedGradient {
+    /// Black over red and orange to yellow
+    Fire,
+    /// Dark blue to cyan
+    Ocean,
+    /// Dark green to light green
+    Forest,
+    /// Purple over red to orange
+    Sunset,
+    /// White to light blue
+    Ice,
+    /// Blue to red like a heat map
+    Heat
+}
+
+impl NamedGradient {
+
+    /// All available gradients
+    pub const ALL: [NamedGradient; 6] = [
+        NamedGradient::Fire,
+        NamedGradient::Ocean,
+        NamedGradient::Forest,
+        NamedGradient::Sunset,
+        NamedGradient::Ice,
+        NamedGradient::Heat
+    ];
+
+    /// Find the gradient by its name, the case is ignored
+    pub fn from_name(name: &str) -> Option<NamedGradient> {
+        NamedGradient::ALL.into_iter()
+            .find(|gradient| gradient.to_string().eq_ignore_ascii_case(name))
+    }
+
+    const FIRE: [Color; 4] = [Color::rgb(0, 0, 0), Color::rgb(255, 0, 0), Color::rgb(255, 128, 0), Color::rgb(255, 255, 0)];
+    const OCEAN: [Color; 3] = [Color::rgb(0, 0, 64), Color::rgb(0, 64, 255), Color::rgb(0, 255, 255)];
+    const FOREST: [Color; 3] = [Color::rgb(0, 48, 0), Color::rgb(32, 160, 32), Color::rgb(160, 255, 64)];
+    const SUNSET: [Color; 3] = [Color::rgb(64, 0, 128), Color::rgb(255, 0, 64), Color::rgb(255, 160, 0)];
+    const ICE: [Color; 3] = [Color::rgb(255, 255, 255), Color::rgb(128, 200, 255), Color::rgb(0, 96, 255)];
+    const HEAT: [Color; 4] = [Color::rgb(0, 0, 255), Color::rgb(0, 255, 0), Color::rgb(255, 255, 0), Color::rgb(255, 0, 0)];
+
+    /// Colours of the gradient, which are spaced equally
+    fn colors(&self) -> &'static [Color] {
+        match self {
+            NamedGradient::Fire => &Self::FIRE,
+            NamedGradient::Ocean => &Self::OCEAN,
+            NamedGradient::Forest => &Self::FOREST,
+            NamedGradient::Sunset => &Self::SUNSET,
+            NamedGradient::Ice => &Self::ICE,
+            NamedGradient::Heat => &Self::HEAT
+        }
+    }
+
+    pub fn gradient(&self) -> Gradient {
+        // The predefined colours are always valid
+        Gradient::even(self.colors()).unwrap()
+    }
+
+    /// Colour at the position between 0 and 1.
+    /// Doesn't allocate the gradient, so it can be used inside the audio thread.
+    pub fn color_at(&self, position: f32) -> Color {
+        let colors = self.colors();
+        let scaled = position.clamp(0.0, 1.0) * (colors.len() - 1) as f32;
+        let index = (scaled.floor() as usize).min(colors.len() - 2);
+
+        colors[index].mix(&colors[index + 1], scaled - index as f32)
+    }
+}
+
+impl Display for NamedGradient {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NamedGradient::Fire => f.write_str("Fire"),
+            NamedGradient::Ocean => f.write_str("Ocean"),
+            NamedGradient::Forest => f.write_str("Forest"),
+            NamedGradient::Sunset => f.write_str("Sunset"),
+            NamedGradient::Ice => f.write_str("Ice"),
+            NamedGradient::Heat => f.write_str("Heat")
+        }
+    }
+}
+
+
+/// Assigns a colour to every position between 0 and 1
+#[derive(Clone, Debug, PartialEq)]
+pub enum Palette {
+    /// The same colour everywhere
+    Solid(Color),
+    /// Full hue circle of the HSV colour space
+    RainbowHsv { saturation: f32, value: f32 },
+    /// Full hue circle of the HSL colour space
+    RainbowHsl { saturation: f32, lightness: f32 },
+    /// One of the predefined gradients
+    Named(NamedGradient),
+    /// Gradient with user defined stops
+    Gradient(Gradient)
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::Solid(Color::WHITE)
+    }
+}
+
+impl Palette {
+
+    /// Saturated HSV rainbow
+    pub const RAINBOW: Palette = Palette::RainbowHsv { saturation: 1.0, value: 1.0 };
+
+    /// Colour at the position between 0 and 1
+    pub fn color_at(&self, position: f32) -> Color {
+        let position = match position.is_nan() {
+            true => 0.0,
+            false => position.clamp(0.0, 1.0)
+        };
+
+        match self {
+            Palette::Solid(color) => *color,
+            Palette::RainbowHsv { saturation, value } => Color::hsv(position * 360.0, *saturation, *value),
+            Palette::RainbowHsl { saturation, lightness } => Color::hsl(position * 360.0, *saturation, *lightness),
+            Palette::Named(named) => named.color_at(position),
+            Palette::Gradient(gradient) => gradient.color_at(position)
+        }
+    }
+}
+
+
+/// Defines what selects the colour of a led out of the palette
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ColorMapping {
+    /// The intensity of the led, e.g. quiet leds are blue and loud leds are red
+    #[default]
+    Intensity,
+    /// The position of the led inside the effect, e.g. the mel bin of a frequency effect
+    Position
+}
+
+
+/// Palette and mapping of an effect.
+/// The brightness of a led always follows its intensity.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ColorScheme {
+    pub palette: Palette,
+    pub mapping: ColorMapping
+}
+
+impl ColorScheme {
+
+    pub fn new(palette: Palette, mapping: ColorMapping) -> ColorScheme {
+        ColorScheme { palette, mapping }
+    }
+
+    /// Colour of a led at the position between 0 and 1 with the intensity of an effect
+    pub fn color(&self, position: f32, intensity: i16) -> Color {
+        let level = intensity.max(0) as f32 / i16::MAX as f32;
+        let selector = match self.mapping {
+            ColorMapping::Intensity => level,
+            ColorMapping::Position => position
+        };
+
+        self.palette.color_at(selector).scale(level)
+    }
+
+    /// Colourize the output of an effect.
+    /// The first value has the position 0 and the last value the position 1.
+    pub fn apply(&self, values: &[i16], pixels: &mut [Color]) {
+        let steps = (values.len().max(2) - 1) as f32;
+
+        for (i, (pixel, value)) in pixels.iter_mut().zip(values.iter()).enumerate() {
+            *pixel = self.color(i as f32 / steps, *value);
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt::{Display, Formatter};
+
+
+/// Colour of a single led with 16 bit per channel.
+/// The higher precision is kept until the frame is converted to bytes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Color {
+    pub red: u16,
+    pub green: u16,
+    pub blue: u16,
+    /// Only used by leds with a separate white channel
+    pub white: u16
+}
+
+impl Color {
+    pub const BLACK: Color = Color::new(0, 0, 0);
+    pub const WHITE: Color = Color::new(u16::MAX, u16::MAX, u16::MAX);
+
+    /// Create a colour with 16 bit channels
+    pub const fn new(red: u16, green: u16, blue: u16) -> Color {
+        Color { red, green, blue, white: 0 }
+    }
+
+    /// Create a colour with 8 bit channels
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color::new(red as u16 * 257, green as u16 * 257, blue as u16 * 257)
+    }
+
+    /// Create a colour of the HSV colour space.
+    /// The hue is given in degrees, saturation and value between 0 and 1.
+    pub fn hsv(hue: f32, saturation: f32, value: f32) -> Color {
+        let value = value.clamp(0.0, 1.0);
+        let chroma = value * saturation.clamp(0.0, 1.0);
+
+        Color::from_chroma(hue, chroma, value - chroma)
+    }
+
+    /// Create a colour of the HSL colour space.
+    /// The hue is given in degrees, saturation and lightness between 0 and 1.
+    pub fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation.clamp(0.0, 1.0);
+
+        Color::from_chroma(hue, chroma, lightness - chroma / 2.0)
+    }
+
+    /// Common part of the HSV and HSL conversion.
+    /// The offset is added to every channel.
+    fn from_chroma(hue: f32, chroma: f32, offset: f32) -> Color {
+        let sector = hue.rem_euclid(360.0) / 60.0;
+        let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+        let (red, green, blue) = match sector as u32 {
+            0 => (chroma, second, 0.0),
+            1 => (second, chroma, 0.0),
+            2 => (0.0, chroma, second),
+            3 => (0.0, second, chroma),
+            4 => (second, 0.0, chroma),
+            _ => (chroma, 0.0, second)
+        };
+        let channel = |value: f32| ((value + offset).clamp(0.0, 1.0) * u16::MAX as f32).round() as u16;
+
+        Color::new(channel(red), channel(green), channel(blue))
+    }
+
+    /// Linear interpolation to the other colour. A factor of 0 keeps this colour
+    pub fn mix(&self, other: &Color, factor: f32) -> Color {
+        let factor = factor.clamp(0.0, 1.0);
+        let mix = |a: u16, b: u16| (a as f32 + (b as f32 - a as f32) * factor).round() as u16;
+
+        Color {
+            red: mix(self.red, other.red),
+            green: mix(self.green, other.green),
+            blue: mix(self.blue, other.blue),
+            white: mix(self.white, other.white)
+        }
+    }
+
+    /// Scale the colour with a factor between 0 and 1
+    pub fn scale(&self, factor: f32) -> Color {
+        let factor = factor.clamp(0.0, 1.0);
+        let scale = |value: u16| (value as f32 * factor).round() as u16;
+
+        Color {
+            red: scale(self.red),
+            green: scale(self.green),
+            blue: scale(self.blue),
+            white: scale(self.white)
+        }
+    }
+
+    /// Scale the colour with the intensity of an effect between 0 and i16::MAX
+    pub fn with_intensity(&self, intensity: i16) -> Color {
+        self.scale(intensity as f32 / i16::MAX as f32)
+    }
+
+    /// Move the common part of red, green and blue to the white channel
+    pub fn extract_white(&self) -> Color {
+        let white = self.red.min(self.green).min(self.blue);
+
+        Color {
+            red: self.red - white,
+            green: self.green - white,
+            blue: self.blue - white,
+            white: self.white.saturating_add(white)
+        }
+    }
+
+    /// Channels in the order red, green, blue, white
+    pub fn channels(&self) -> [u16; 4] {
+        [self.red, self.green, self.blue, self.white]
+    }
+
+    /// Create the colour from the channels in the order red, green, blue, white
+    pub fn from_channels(channels: [u16; 4]) -> Color {
+        Color { red: channels[0], green: channels[1], blue: channels[2], white: channels[3] }
+    }
+}
+
+
+/// Order of the colour channels, which the led chipset expects
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ChannelOrder {
+    #[default]
+    Rgb,
+    Rbg,
+    Grb,
+    Gbr,
+    Brg,
+    Bgr
+}
+
+impl ChannelOrder {
+
+    /// All available channel orders
+    pub const ALL: [ChannelOrder; 6] = [
+        ChannelOrder::Rgb,
+        ChannelOrder::Rbg,
+        ChannelOrder::Grb,
+        ChannelOrder::Gbr,
+        ChannelOrder::Brg,
+        ChannelOrder::Bgr
+    ];
+
+    /// Indices of the red (0), green (1) and blue (2) channel in the transmitted order
+    fn indices(&self) -> [usize; 3] {
+        match self {
+            ChannelOrder::Rgb => [0, 1, 2],
+            ChannelOrder::Rbg => [0, 2, 1],
+            ChannelOrder::Grb => [1, 0, 2],
+            ChannelOrder::Gbr => [1, 2, 0],
+            ChannelOrder::Brg => [2, 0, 1],
+            ChannelOrder::Bgr => [2, 1, 0]
+        }
+    }
+}
+
+impl Display for ChannelOrder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChannelOrder::Rgb => f.write_str("RGB"),
+            ChannelOrder::Rbg => f.write_str("RBG"),
+            ChannelOrder::Grb => f.write_str("GRB"),
+            ChannelOrder::Gbr => f.write_str("GBR"),
+            ChannelOrder::Brg => f.write_str("BRG"),
+            ChannelOrder::Bgr => f.write_str("BGR")
+        }
+    }
+}
+
+
+/// Describes how a pixel is transmitted to the leds
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PixelFormat {
+    /// Order of the colour channels
+    pub order: ChannelOrder,
+    /// The leds have a fourth white channel, which is sent after the colours
+    pub white: bool
+}
+
+impl PixelFormat {
+    pub const RGB: PixelFormat = PixelFormat { order: ChannelOrder::Rgb, white: false };
+    pub const GRB: PixelFormat = PixelFormat { order: ChannelOrder::Grb, white: false };
+    pub const RGBW: PixelFormat = PixelFormat { order: ChannelOrder::Rgb, white: true };
+    pub const GRBW: PixelFormat = PixelFormat { order: ChannelOrder::Grb, white: true };
+
+    /// Amount of bytes per pixel
+    pub fn channels(&self) -> usize {
+        match self.white {
+            true => 4,
+            false => 3
+        }
+    }
+
+    /// Write the channels of the colour in the transmitted order.
+    /// The output must have the length of *channels*.
+    pub fn order_channels<T: Copy>(&self, channels: [T; 4], output: &mut [T]) {
+        for (value, index) in output.iter_mut().zip(self.order.indices()) {
+            *value = channels[index];
+        }
+        if self.white {
+            output[3] = channels[3];
+        }
+    }
+}
+
+
+/// Colours of all leds of one frame
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PixelFrame {
+    pixels: Vec<Color>
+}
+
+impl PixelFrame {
+
+    /// Create a black frame with the amount of pixels
+    pub fn new(length: usize) -> PixelFrame {
+        PixelFrame { pixels: vec![Color::BLACK; length] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    /// Change the amount of pixels. New pixels are black
+    pub fn resize(&mut self, length: usize) {
+        self.pixels.resize(length, Color::BLACK)
+    }
+
+    pub fn pixels(&self) -> &[Color] {
+        self.pixels.as_slice()
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        self.pixels.as_mut_slice()
+    }
+
+    /// Set every pixel to the colour
+    pub fn fill(&mut self, color: Color) {
+        self.pixels.fill(color)
+    }
+
+    /// Convert the frame to the bytes of the pixel format.
+    /// Every channel is rounded to 8 bit.
+    pub fn to_bytes(&self, format: PixelFormat, output: &mut Vec<u8>) {
+        self.quantize(format, output, |_, value| to_byte(value))
+    }
+
+    /// Convert the frame to the bytes of the pixel format with a custom quantization.
+    /// The quantization gets the index of the channel inside the frame (4 channels per pixel)
+    /// and its 16 bit value.
+    pub fn quantize<Q>(&self, format: PixelFormat, output: &mut Vec<u8>, mut quantization: Q)
+        where Q: FnMut(usize, u16) -> u8
+    {
+        let channels = format.channels();
+        output.clear();
+        output.resize(self.pixels.len() * channels, 0);
+
+        for (i, (pixel, bytes)) in self.pixels.iter().zip(output.chunks_exact_mut(channels)).enumerate() {
+            let pixel = match format.white {
+                true => pixel.extract_white(),
+                false => *pixel
+            };
+
+            let mut values = [0; 4];
+            for (channel, (byte, value)) in values.iter_mut().zip(pixel.channels()).enumerate() {
+                *byte = quantization(i * 4 + channel, value);
+            }
+            format.order_channels(values, bytes);
+        }
+    }
+}
+
+
+/// Round a 16 bit channel to 8 bit
+pub fn to_byte(value: u16) -> u8 {
+    ((value as u32 + 128) / 257) as u8
+}
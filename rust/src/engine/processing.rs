@@ -1 +1,134 @@
-pub const N_FFT: usize = 512;
\ No newline at end of file
+use std::sync::Arc;
+
+use realfft::{RealFftPlanner, RealToComplex};
+use realfft::num_complex::Complex;
+
+pub mod mel;
+pub mod window;
+
+use window::Window;
+
+/// Amount of points of the fourier transformation.
+/// Shorter frames are padded with zeros, longer frames only keep their newest N_FFT samples.
+pub const N_FFT: usize = 1024;
+
+/// Amount of the positive frequency bins of the fourier transformation
+pub const N_BINS: usize = N_FFT / 2;
+
+
+/// Calculates the spectrum of an audio frame.
+/// Equivalent to the *rfft* and *get_power_frames* functions of the notebook.
+pub struct Spectrum {
+    fft: Arc<dyn RealToComplex<f32>>,
+
+    // Selected window and its precomputed coefficients
+    window: Window,
+    coefficients: Vec<f32>,
+
+    // Buffers of the fourier transformation
+    input: Vec<f32>,
+    output: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>
+}
+
+impl Spectrum {
+
+    /// Plan a new fourier transformation with N_FFT points
+    /// and precompute the window for frames with the given length.
+    pub fn new(window: Window, frame_length: usize) -> Spectrum {
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(N_FFT);
+
+        Spectrum {
+            input: fft.make_input_vec(),
+            output: fft.make_output_vec(),
+            scratch: fft.make_scratch_vec(),
+            window,
+            coefficients: window.coefficients(frame_length.min(N_FFT)),
+            fft
+        }
+    }
+
+    /// Selected window function
+    pub fn window(&self) -> Window {
+        self.window
+    }
+
+    /// Power of a full scale sine, which fills the whole frame.
+    /// Depends on the sum of the window coefficients.
+    pub fn max_power(&self) -> f32 {
+        let sum: f32 = self.coefficients.iter().sum();
+        power_of(sum / 2.0)
+    }
+
+    /// Calculate the magnitude of all positive frequencies.
+    /// The frame is windowed and padded with zeros to N_FFT.
+    /// Longer frames are cut to their newest N_FFT samples, so the spectrum doesn't lag behind the audio.
+    pub fn magnitude(&mut self, frame: &[i16], magnitude: &mut [f32; N_BINS]) {
+        let frame = &frame[frame.len().saturating_sub(N_FFT)..];
+        let length = frame.len();
+        if self.coefficients.len() != length {
+            self.coefficients = self.window.coefficients(length);
+        }
+
+        // Convert the integer samples to floats between -1 and 1
+        for (i, value) in self.input.iter_mut().enumerate() {
+            *value = match i < length {
+                true => frame[i] as f32 / 32768.0 * self.coefficients[i],
+                false => 0.0
+            };
+        }
+
+        // The buffers are created by the plan itself, so their lengths are always valid
+        self.fft.process_with_scratch(&mut self.input, &mut self.output, &mut self.scratch)
+            .unwrap();
+
+        // Only the positive frequencies
+        for (value, bin) in magnitude.iter_mut().zip(self.output.iter()) {
+            *value = bin.norm();
+        }
+    }
+
+    /// Calculate the power of all positive frequencies.
+    pub fn power(&mut self, frame: &[i16], power: &mut [f32; N_BINS]) {
+        self.magnitude(frame, power);
+
+        for value in power.iter_mut() {
+            *value = power_of(*value);
+        }
+    }
+
+}
+
+/// Power of a magnitude like in *get_power_frames*
+fn power_of(magnitude: f32) -> f32 {
+    (1.0 / (N_FFT as f32 / 2.0) * 2.0 + 1.0) * magnitude.powi(2)
+}
+
+
+/// Change the length of the values with linear interpolation like *interpolate* in the notebook
+pub fn interpolate(values: &[f32], output: &mut [f32]) {
+    if values.is_empty() || output.is_empty() { return }
+
+    let last = (values.len() - 1) as f32;
+    let steps = (output.len().max(2) - 1) as f32;
+
+    for (i, value) in output.iter_mut().enumerate() {
+        let position = i as f32 / steps * last;
+        let left = position.floor() as usize;
+        let right = (left + 1).min(values.len() - 1);
+        let fraction = position - left as f32;
+
+        *value = values[left] * (1.0 - fraction) + values[right] * fraction;
+    }
+}
+
+/// Convert power values to the i16 range with a logarithmic scale.
+/// The max power reaches i16::MAX, see *Spectrum::max_power*.
+pub fn quantize(power: &[f32], max_power: f32, output: &mut [i16]) {
+    let scale = (1.0 + max_power).log10();
+
+    for (value, out) in power.iter().zip(output.iter_mut()) {
+        let normalized = ((1.0 + value).log10() / scale).min(1.0);
+        *out = (normalized * i16::MAX as f32) as i16;
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+
+use crate::engine::errors::ApplicationError;
+use super::{N_BINS, N_FFT};
+
+/// Default lowest frequency of the filterbank in Hz
+pub const DEFAULT_MIN_FREQUENCY: f32 = 20.0;
+/// Default highest frequency of the filterbank in Hz
+pub const DEFAULT_MAX_FREQUENCY: f32 = 12000.0;
+
+
+/// Convert a frequency in Hz to the mel scale
+pub fn hz_to_mel(frequency: f32) -> f32 {
+    2595.0 * (1.0 + frequency / 700.0).log10()
+}
+
+/// Convert a mel value to the frequency in Hz
+pub fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+
+/// Single filter of the filterbank.
+/// Contains the weights for all fft bins beginning at *start*
+struct MelFilter {
+    start: usize,
+    weights: Vec<f32>
+}
+
+/// Triangular filters on the mel scale, which convert the power spectrum into mel bins.
+/// Every filter is normalized, so the result is the weighted average power of the band.
+pub struct MelFilterbank {
+    filters: Vec<MelFilter>
+}
+
+impl MelFilterbank {
+
+    /// Create a filterbank with *n_mel* filters between the min and max frequency.
+    /// The max frequency will be limited to the nyquist frequency of the sample rate.
+    pub fn new(sample_rate: u32, n_mel: usize, min_frequency: f32, max_frequency: f32) -> Result<Self> {
+        let nyquist = sample_rate as f32 / 2.0;
+        let max_frequency = max_frequency.min(nyquist);
+
+        if min_frequency < 0.0 || min_frequency >= max_frequency {
+            Err(ApplicationError::InvalidFrequencyRange {
+                min: min_frequency,
+                max: max_frequency
+            })?
+        }
+
+        // n_mel filters need n_mel+2 points: Every filter starts at the center of the previous filter
+        let min_mel = hz_to_mel(min_frequency);
+        let max_mel = hz_to_mel(max_frequency);
+        let points: Vec<f32> = (0..n_mel+2)
+            .map(|i| min_mel + (max_mel - min_mel) * i as f32 / (n_mel + 1) as f32)
+            .map(|mel| mel_to_hz(mel) / nyquist * N_BINS as f32)
+            .collect();
+
+        let filters = points.windows(3)
+            .map(|point| Self::triangle(point[0], point[1], point[2]))
+            .collect();
+
+        Ok(MelFilterbank { filters })
+    }
+
+    /// Amount of mel bins
+    pub fn n_mel(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Convert the power spectrum to the mel bins
+    pub fn apply(&self, power: &[f32; N_BINS], output: &mut [f32]) {
+        for (filter, value) in self.filters.iter().zip(output.iter_mut()) {
+            *value = filter.weights.iter()
+                .zip(power[filter.start..].iter())
+                .map(|(weight, power)| weight * power)
+                .sum();
+        }
+    }
+
+    /// Create a triangular filter between the left and right position, which peaks at the center.
+    /// The positions are fractional fft bins.
+    fn triangle(left: f32, center: f32, right: f32) -> MelFilter {
+        let start = (left.ceil() as usize).min(N_BINS - 1);
+        let end = (right.floor() as usize).min(N_BINS - 1);
+
+        let mut weights: Vec<f32> = (start..=end)
+            .map(|bin| {
+                let bin = bin as f32;
+                match bin <= center {
+                    true => (bin - left) / (center - left),
+                    false => (right - bin) / (right - center)
+                }
+            })
+            .map(|weight| weight.max(0.0))
+            .collect();
+
+        let sum: f32 = weights.iter().sum();
+
+        // The filter is smaller than one bin, which happens with very many mel bins.
+        // Interpolate between the two bins around the center instead.
+        if sum <= f32::EPSILON {
+            let start = (center.floor() as usize).min(N_BINS - 2);
+            let fraction = (center - start as f32).clamp(0.0, 1.0);
+
+            return MelFilter {
+                start,
+                weights: vec![1.0 - fraction, fraction]
+            }
+        }
+
+        for weight in weights.iter_mut() {
+            *weight /= sum;
+        }
+
+        MelFilter { start, weights }
+    }
+
+}
+
+
+/// Key of a cached filterbank: Sample rate, N_FFT, n_mel and the bits of the min and max frequency
+type MelKey = (u32, usize, usize, u32, u32);
+
+/// Cache of the already calculated filterbanks.
+/// Effects with the same amount of mel bins share one filterbank.
+#[derive(Default)]
+pub struct MelCache {
+    filterbanks: HashMap<MelKey, Arc<MelFilterbank>>
+}
+
+impl MelCache {
+
+    pub fn new() -> Self {
+        MelCache::default()
+    }
+
+    /// Get the filterbank for the configuration or create a new one
+    pub fn get(&mut self, sample_rate: u32, n_mel: usize, min_frequency: f32, max_frequency: f32) -> Result<Arc<MelFilterbank>> {
+        let key = (sample_rate, N_FFT, n_mel, min_frequency.to_bits(), max_frequency.to_bits());
+
+        if let Some(filterbank) = self.filterbanks.get(&key) {
+            return Ok(filterbank.clone())
+        }
+
+        let filterbank = Arc::new(MelFilterbank::new(sample_rate, n_mel, min_frequency, max_frequency)?);
+        self.filterbanks.insert(key, filterbank.clone());
+
+        Ok(filterbank)
+    }
+
+}
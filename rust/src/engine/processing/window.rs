@@ -0,0 +1,58 @@
+use std::f32::consts::PI;
+use std::fmt::{Display, Formatter};
+
+
+/// Window functions, which are applied to the frame before the fourier transformation
+/// to reduce the spectral leakage.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Window {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+    BlackmanHarris
+}
+
+impl Window {
+
+    /// All available windows
+    pub const ALL: [Window; 5] = [
+        Window::Rectangular,
+        Window::Hann,
+        Window::Hamming,
+        Window::Blackman,
+        Window::BlackmanHarris
+    ];
+
+    /// Calculate the periodic window with the given length
+    pub fn coefficients(&self, length: usize) -> Vec<f32> {
+        (0..length)
+            .map(|n| {
+                let x = 2.0 * PI * n as f32 / length as f32;
+                match self {
+                    Window::Rectangular => 1.0,
+                    Window::Hann => 0.5 - 0.5 * x.cos(),
+                    Window::Hamming => 0.54 - 0.46 * x.cos(),
+                    Window::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+                    Window::BlackmanHarris => {
+                        0.35875 - 0.48829 * x.cos() + 0.14128 * (2.0 * x).cos() - 0.01168 * (3.0 * x).cos()
+                    }
+                }
+            })
+            .collect()
+    }
+
+}
+
+impl Display for Window {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Window::Rectangular => f.write_str("Rectangular"),
+            Window::Hann => f.write_str("Hann"),
+            Window::Hamming => f.write_str("Hamming"),
+            Window::Blackman => f.write_str("Blackman"),
+            Window::BlackmanHarris => f.write_str("Blackman-Harris")
+        }
+    }
+}
@@ -1,13 +1,56 @@
 use std::net::{IpAddr, SocketAddr};
+use std::ops::Range;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use sacn_unofficial::source::SacnSource;
 use sacn_unofficial::packet::{ACN_SDT_MULTICAST_PORT, UNIVERSE_CHANNEL_CAPACITY};
 
 use super::errors::ApplicationError;
-use super::utils::count_true;
 use anyhow::Result;
 use crate::engine::errors::SenderError;
 
+/// Consecutive universes of a sender
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UniverseRange {
+    /// First universe, the sacn universes start at 1
+    pub start: u16,
+    /// Amount of universes
+    pub count: u16
+}
+
+impl UniverseRange {
+
+    pub fn new(start: u16, count: u16) -> Self {
+        UniverseRange { start, count }
+    }
+
+    /// Universe behind the last universe of the range
+    pub fn end(&self) -> u32 {
+        self.start as u32 + self.count as u32
+    }
+
+    /// All universes of the range
+    pub fn universes(&self) -> Range<u32> {
+        self.start as u32..self.end()
+    }
+
+    /// Check if both ranges share a universe
+    pub fn overlaps(&self, other: &UniverseRange) -> bool {
+        (self.start as u32) < other.end() && (other.start as u32) < self.end()
+    }
+
+    /// Check if the range is part of the sacn universes
+    fn validate(&self) -> Result<(), SenderError> {
+        if self.count == 0 || (self.start as u32) < SenderInner::FIRST_UNIVERSE || self.end() - 1 > SenderInner::LAST_UNIVERSE {
+            Err(SenderError::InvalidUniverses(*self))?
+        }
+
+        Ok(())
+    }
+
+}
+
+
 pub struct Sender {
     // Inner sender which sends the data to the network
     inner: Arc<Mutex<SenderInner>>,
@@ -19,10 +62,25 @@ pub struct Sender {
 impl Sender {
     const SENDER_NAME: &'static str = "sender";
 
-    /// Create a new Sender
+    /// Create a new Sender, which begins at universe 1.
+    /// Its universes grow with the length of the sent data.
     /// Could throw a IOError if the underlying UDP Socket can't be created
     pub fn new() -> Result<Self> {
-        let inner = SenderInner::new(Self::SENDER_NAME)?;
+        let owner = Owner::automatic(UniverseRange::new(SenderInner::FIRST_UNIVERSE as u16, 1));
+        Self::with_owner(owner)
+    }
+
+    /// Create a new Sender, which only uses the given universes
+    /// Overlaps are only detected between a sender and its clones. Independent senders aren't checked,
+    /// so they can send to the same universes. Use *clone_with_universes* to share the universes of one installation.
+    /// Could throw a IOError if the underlying UDP Socket can't be created or an InvalidUniverses Error
+    pub fn with_universes(range: UniverseRange) -> Result<Self> {
+        range.validate()?;
+        Self::with_owner(Owner::fixed(range))
+    }
+
+    fn with_owner(owner: Owner) -> Result<Self> {
+        let inner = SenderInner::new(Self::SENDER_NAME, owner)?;
         let arc = Arc::new(Mutex::new(inner));
 
         Ok(
@@ -35,8 +93,9 @@ impl Sender {
     }
 
     /// Send the data
-    /// Note that the length can't be larger then 512 (*SenderInner::PacketCapacity*)
-    /// Could thrown an IOError or a WrongPacketSize Error
+    /// Data which is longer than one universe is split across consecutive universes,
+    /// see *set_channels_per_universe*.
+    /// Could thrown an IOError or a WrongPacketSize Error, if the universes would exceed the sacn range
     pub fn send(&self, data: &[u8]) -> Result<()> {
         let mut inner = self.inner.lock().unwrap();
         inner.add(self.owner_id, data)?;
@@ -44,13 +103,77 @@ impl Sender {
         Ok(())
     }
 
+    /// Turn off all channels of this sender.
+    /// The packet is sent immediately, even if it isn't due or the other senders haven't written their data yet.
+    pub fn blackout(&self) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.blackout(self.owner_id)?;
+
+        Ok(())
+    }
+
+    /// Limit the sent packets to the frame rate, e.g. the refresh limit of the led controller.
+    /// Packets which are completed before the next frame is due will be dropped.
+    /// The frame rate is shared by all clones of the sender.
+    pub fn set_frame_rate(&self, frame_rate: u32) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.set_frame_rate(frame_rate)?;
+
+        Ok(())
+    }
+
+    /// Get the frame rate of the sender.
+    /// Without a frame rate every completed packet will be sent immediately.
+    pub fn frame_rate(&self) -> Option<u32> {
+        let inner = self.inner.lock().unwrap();
+        inner.frame_rate
+    }
+
+    /// Set the amount of channels per universe between 1 and 512, which are filled with data.
+    /// E.g. 510 channels fit exactly 170 rgb pixels, so no pixel is split between two universes.
+    /// The amount is shared by all clones of the sender.
+    pub fn set_channels_per_universe(&self, channels: usize) -> Result<()> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.set_channels_per_universe(channels)?;
+
+        Ok(())
+    }
+
+    /// Get the amount of channels per universe
+    pub fn channels_per_universe(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner.channels_per_universe
+    }
+
+    /// Get the universes of this sender.
+    /// Without an explicit range they grow with the length of the sent data, but always keep their start.
+    pub fn universes(&self) -> Vec<u16> {
+        let inner = self.inner.lock().unwrap();
+        inner.owners[self.owner_id].range.universes().map(|universe| universe as u16).collect()
+    }
+
+    /// Amount of packets, which were sent by this sender and its clones
+    pub fn sent_packets(&self) -> u64 {
+        let inner = self.inner.lock().unwrap();
+        inner.sent_packets
+    }
+
+    /// Amount of channels, which fit into the current universes of this sender
+    pub fn capacity(&self) -> usize {
+        let inner = self.inner.lock().unwrap();
+        inner.owner_capacity(self.owner_id)
+    }
+
     /// Clone the sender and register the cloned object as new sender
+    /// The clone begins behind the last universe of all senders and grows with its data.
+    /// Senders never move to other universes, so a sender can only grow until the universes of the next one.
+    /// Create the clone after the first frame was sent or use *clone_with_universes* for longer data.
     /// Could throw an error if the underlying inner already reached the maximum of owners
     pub fn clone(&self) -> Result<Self> {
         // Add new owner to the inner, then
         // copy the arc to the inner and construct a new sender
 
-        let id = self.add_owner()?;
+        let id = self.add_owner(None)?;
         let inner_clone = self.inner.clone();
 
         Ok(Sender {
@@ -59,13 +182,25 @@ impl Sender {
         })
     }
 
+    /// Clone the sender, but the clone only uses the given universes
+    /// Could throw an error if the range overlaps the universes of this sender or one of its clones
+    /// or the underlying inner already reached the maximum of owners
+    pub fn clone_with_universes(&self, range: UniverseRange) -> Result<Self> {
+        let id = self.add_owner(Some(range))?;
+
+        Ok(Sender {
+            inner: self.inner.clone(),
+            owner_id: id
+        })
+    }
+
     /// Add a new owner to the inner
-    fn add_owner(&self) -> Result<usize> {
+    fn add_owner(&self, range: Option<UniverseRange>) -> Result<usize> {
         // Reference to the inner of the sender. If another engine(thread) uses it, we have to wait
         // until the last owner has finished
         // If an error will be returned another thread panicked, so unwrap will be necessary
         let mut inner = self.inner.lock().unwrap();
-        let owner_id = inner.add_owner()?;
+        let owner_id = inner.add_owner(range)?;
 
         Ok(owner_id)
     }
@@ -98,122 +233,304 @@ fn new_source(name: &'static str) -> Result<SacnSource, SenderError> {
 
 
 
+/// Universes and the current data of one owner
+struct Owner {
+    // Universes of the owner
+    range: UniverseRange,
+    // The range was given explicitly, so it won't grow with the data
+    fixed: bool,
+    // Data of the owner for the next packet
+    data: Vec<u8>,
+    // The owner already wrote its data to the current packet
+    complete: bool
+}
+
+impl Owner {
+    fn automatic(range: UniverseRange) -> Self {
+        Owner { range, fixed: false, data: vec![], complete: false }
+    }
+
+    fn fixed(range: UniverseRange) -> Self {
+        Owner { range, fixed: true, data: vec![], complete: false }
+    }
+}
+
+
 struct SenderInner {
     // Underlying sacn sender source
     source: SacnSource,
-    // Numbers of references which can use this struct
-    owners: usize,
+    // All references which can use this struct. The index is the owner id
+    owners: Vec<Owner>,
+
+    // Amount of channels of every universe, which are filled with data
+    channels_per_universe: usize,
 
-    // Number of owners, which already wrote their data to the current packet
-    packet_complete: [bool; SenderInner::MAX_OWNERS],
+    // Universes, which are already registered at the source
+    registered: Vec<u16>,
 
-    //array which stores all data to send
-    packet: [u8; UNIVERSE_CHANNEL_CAPACITY*SenderInner::MAX_OWNERS],
+    // Universes and data of the current packet, every universe begins with the start code
+    universes: Vec<u16>,
+    packet: Vec<u8>,
 
-    // Registered universes
-    universes: [u16; SenderInner::MAX_OWNERS]
+    // Pacing of the packets
+    frame_rate: Option<u32>,
+    next_packet: Option<Instant>,
+
+    // Amount of packets, which were sent by the source
+    sent_packets: u64
 }
 
 impl SenderInner {
     /// Maximum of allowed owners to send
     const MAX_OWNERS: usize = 3;
-    /// Maximum size of the packet capacity
-    const PACKET_CAPACITY: usize = UNIVERSE_CHANNEL_CAPACITY-1;
-
-    ///Create a new SenderInner
-    fn new(name: &'static str) -> Result<Self, SenderError> {
-        let mut source = new_source(name)?;
-        let owners = 1;
-        let matrix = [0 as u8; UNIVERSE_CHANNEL_CAPACITY*SenderInner::MAX_OWNERS];
-        let packet_complete = [false; SenderInner::MAX_OWNERS];
-
-        //Generate necessary universes
-        let mut universes = [1 as u16; Self::MAX_OWNERS];
-        for i in 0..Self::MAX_OWNERS  {
-            //Universes starts at 1
-            universes[i] = (i+1) as u16;
-        }
-        // Register all necessary universes
-        source.register_universes(universes.as_slice()).unwrap();
+    /// Amount of dmx channels of a universe without the start code
+    const UNIVERSE_CAPACITY: usize = UNIVERSE_CHANNEL_CAPACITY-1;
+    /// First universe of the sacn protocol
+    const FIRST_UNIVERSE: u32 = 1;
+    /// Last universe of the sacn protocol
+    const LAST_UNIVERSE: u32 = 63999;
+
+    ///Create a new SenderInner with its first owner
+    fn new(name: &'static str, owner: Owner) -> Result<Self, SenderError> {
+        let source = new_source(name)?;
 
         Ok(
             SenderInner {
                 source,
-                owners,
-                packet: matrix,
-                packet_complete,
-                universes
+                owners: vec![owner],
+                channels_per_universe: Self::UNIVERSE_CAPACITY,
+                registered: vec![],
+                universes: vec![],
+                packet: vec![],
+                frame_rate: None,
+                next_packet: None,
+                sent_packets: 0
             }
         )
 
     }
 
     /// Increase the number of owners to the inner and returns the next owner_id
+    /// Without a range the owner begins behind the last universe of all owners.
     /// Throw MaximumOwner if the maximum of owners is reached
-    fn add_owner(&mut self) -> Result<usize> {
-        if self.owners == Self::MAX_OWNERS {
+    /// or InvalidUniverses and UniverseOverlap if the range can't be used
+    fn add_owner(&mut self, range: Option<UniverseRange>) -> Result<usize> {
+        if self.owners.len() == Self::MAX_OWNERS {
             Err(ApplicationError::MaximumEngines)?
         }
 
-        // Increase the number of owners
-        // Because the length (owners) is 1 more than the highest id, we can use the old length as new id.
-        let new_owner_id = self.owners;
+        let owner = match range {
+            Some(range) => Owner::fixed(range),
+            None => {
+                // The ranges end at most with the universe 64000, so the start fits into an u16
+                let start = self.owners.iter().map(|owner| owner.range.end()).max().unwrap_or(Self::FIRST_UNIVERSE);
+                Owner::automatic(UniverseRange::new(start as u16, 1))
+            }
+        };
+        owner.range.validate()?;
+        self.check_overlap(None, &owner.range)?;
 
-        // Increase the number of owners
-        self.owners+=1;
-        self.source.register_universe(self.owners as u16).unwrap();
+        // Because the length (owners) is 1 more than the highest id, we can use the old length as new id.
+        let new_owner_id = self.owners.len();
+        self.owners.push(owner);
 
         // Return next owner id
         Ok(new_owner_id)
     }
 
+    /// Check that no other owner than the given one uses a universe of the range
+    fn check_overlap(&self, owner_id: Option<usize>, range: &UniverseRange) -> Result<(), SenderError> {
+        let overlapping = self.owners.iter().enumerate()
+            .filter(|(id, _)| Some(*id) != owner_id)
+            .any(|(_, owner)| owner.range.overlaps(range));
+
+        if overlapping {
+            Err(SenderError::UniverseOverlap(*range))?
+        }
+
+        Ok(())
+    }
+
+    /// Amount of channels, which can be sent by the owner
+    fn owner_capacity(&self, owner_id: usize) -> usize {
+        self.owners[owner_id].range.count as usize * self.channels_per_universe
+    }
+
 
     /// Add the data to the next packet
     fn add(&mut self, owner_id: usize, data: &[u8]) -> Result<(), SenderError> {
         // If not all owners have written their data, the packet will not be send.
-        if count_true(self.packet_complete.as_slice()) < self.owners {
+        if !self.owners[owner_id].complete {
             self.add_to_packet(owner_id, data)?;
 
-            self.packet_complete[owner_id] = true;
+            self.owners[owner_id].complete = true;
         }
 
 
         // Send the packet if now all users have written their data
-        if count_true(self.packet_complete.as_slice()) == self.owners {
+        if self.owners.iter().all(|owner| owner.complete) {
             // Send the packet
-            self.send_packet()?;
-            self.packet_complete = [false; SenderInner::MAX_OWNERS];
+            if self.is_packet_due() {
+                self.send_packet()?;
+            }
+            for owner in self.owners.iter_mut() {
+                owner.complete = false;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the data of the owner with zeros and send the packet immediately.
+    /// Neither the frame rate nor the other owners delay the packet.
+    fn blackout(&mut self, owner_id: usize) -> Result<(), SenderError> {
+        let capacity = self.owner_capacity(owner_id);
+        let owner = &mut self.owners[owner_id];
+        owner.data.clear();
+        owner.data.resize(capacity, 0);
+
+        self.send_packet()
+    }
+
+    /// Set the amount of channels per universe, which are filled with data
+    fn set_channels_per_universe(&mut self, channels: usize) -> Result<(), SenderError> {
+        if channels == 0 || channels > Self::UNIVERSE_CAPACITY {
+            Err(SenderError::InvalidChannelsPerUniverse(channels))?
+        }
+
+        self.channels_per_universe = channels;
+
+        Ok(())
+    }
+
+    /// Set the frame rate for the pacing of the packets
+    fn set_frame_rate(&mut self, frame_rate: u32) -> Result<(), SenderError> {
+        if frame_rate == 0 {
+            Err(SenderError::InvalidFrameRate(frame_rate))?
         }
 
+        self.frame_rate = Some(frame_rate);
+        self.next_packet = None;
+
         Ok(())
     }
 
+    /// Check if the next packet can be sent with the current frame rate.
+    /// The packets are scheduled on a fixed grid, so a small jitter of the audio callback
+    /// doesn't drop any frames. After a pause the grid starts again.
+    fn is_packet_due(&mut self) -> bool {
+        let frame_rate = match self.frame_rate {
+            None => return true,
+            Some(value) => value
+        };
+
+        let interval = Duration::from_secs_f64(1.0 / frame_rate as f64);
+        let now = Instant::now();
+
+        match self.next_packet {
+            // Allow packets which are up to a quarter frame too early
+            Some(next) if now + interval / 4 < next => false,
+            Some(next) if now < next + interval => {
+                self.next_packet = Some(next + interval);
+                true
+            }
+            _ => {
+                self.next_packet = Some(now + interval);
+                true
+            }
+        }
+    }
+
+    /// Keep the data of the owner for the next packet.
+    /// If the data doesn't fit into the universes of an owner without a fixed range, the owner gets more universes.
+    /// The additional universes can't be used by another owner.
+    /// If the data gets shorter again, the unused universes are terminated and given back.
+    /// Owners never move, so the data is rejected with WrongPacketSize if the grown range would overlap another owner.
     fn add_to_packet(&mut self, id: usize, data: &[u8]) -> Result<(), SenderError> {
-        if data.len() > Self::PACKET_CAPACITY  {
-            Err(SenderError::WrongPacketSize)?
+        let required = data.len().div_ceil(self.channels_per_universe).max(1);
+        let range = self.owners[id].range;
+
+        if required > range.count as usize {
+            if self.owners[id].fixed || required > u16::MAX as usize {
+                Err(SenderError::WrongPacketSize)?
+            }
+
+            let grown = UniverseRange::new(range.start, required as u16);
+            if grown.validate().is_err() || self.check_overlap(Some(id), &grown).is_err() {
+                Err(SenderError::WrongPacketSize)?
+            }
+            self.owners[id].range = grown;
+        } else if required < range.count as usize && !self.owners[id].fixed {
+            // Shorter data frees the universes at the end, so they don't keep sending zeros
+            let shrunk = UniverseRange::new(range.start, required as u16);
+            self.owners[id].range = shrunk;
+            self.terminate(shrunk.end()..range.end())?;
         }
 
-        // Go the chunk which is reserved for the owner with the *id*
-        let chunk = id*UNIVERSE_CHANNEL_CAPACITY;
+        let owner = &mut self.owners[id];
+        owner.data.clear();
+        owner.data.extend_from_slice(data);
+
+        Ok(())
+    }
 
-        // ID = Chunk of owner + Start code + Iteration
-        for (i, x) in data.iter().enumerate() {
-            self.packet[chunk+1+i] = *x;
+    /// Stop sending on the universes, so the receivers know that the data ends
+    fn terminate(&mut self, universes: Range<u32>) -> Result<(), SenderError> {
+        for universe in universes.map(|universe| universe as u16) {
+            if let Some(position) = self.registered.iter().position(|registered| *registered == universe) {
+                self.registered.remove(position);
+                if let Err(err) = self.source.terminate_stream(universe, 0) {
+                    Err(SenderError::SendError(err.description().to_string()))?
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Arrange the data of all owners in their universes
+    fn build_packet(&mut self) {
+        self.universes.clear();
+        self.packet.clear();
+
+        for owner in self.owners.iter() {
+            let mut chunks = owner.data.chunks(self.channels_per_universe);
+
+            for universe in owner.range.universes() {
+                // Every universe begins with the start code 0
+                let start = self.packet.len() + 1;
+                self.packet.resize(self.packet.len() + UNIVERSE_CHANNEL_CAPACITY, 0);
+
+                if let Some(chunk) = chunks.next() {
+                    self.packet[start..start + chunk.len()].copy_from_slice(chunk);
+                }
+                self.universes.push(universe as u16);
+            }
+        }
+    }
+
     /// Send the current packet
     fn send_packet(&mut self) -> Result<(), SenderError> {
+        self.build_packet();
+
+        // Universes are registered as soon as they are used
+        for universe in self.universes.iter() {
+            if !self.registered.contains(universe) {
+                if let Err(err) = self.source.register_universe(*universe) {
+                    Err(SenderError::SendError(err.description().to_string()))?
+                }
+                self.registered.push(*universe);
+            }
+        }
+
         match self.source.send(
-            &self.universes.as_slice(),
+            self.universes.as_slice(),
             self.packet.as_slice(),
             None,
             None,
             None
         ) {
-            Ok(_) => (),
+            Ok(_) => self.sent_packets += 1,
             Err(err) => {
                 Err(
                     SenderError::SendError(err.description().to_string())
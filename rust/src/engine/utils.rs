@@ -5,9 +5,19 @@ pub fn count_true(slice: &[bool]) -> usize {
     slice.iter().filter(|b| **b).count()
 }
 
+/// Describes how the audio data is framed into windows.
+/// Every *hop_length* new samples, a window with the last *window_length* samples is created.
+/// Windows with multiple channels are stored one channel after another.
+#[derive(Copy, Clone, Debug)]
 pub struct BufferInfo {
-    pub frame_length: usize,
-    pub frame_capture_size: usize
+    /// Amount of samples of one channel inside one window
+    pub window_length: usize,
+    /// Amount of new samples of one channel between two windows
+    pub hop_length: usize,
+    /// Sample rate of the buffered audio data
+    pub sample_rate: u32,
+    /// Amount of buffered channels
+    pub channels: usize
 }
 
 /// Enum to categories fir different domains
@@ -26,37 +36,128 @@ impl Display for Domain {
     }
 }
 
+/// Defines how the channels of a stereo stream are displayed
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StereoLayout {
+    /// The left channel is rendered on the first half of the leds, the right channel on the second half.
+    /// On a two dimensional canvas the halves are the left and the right columns.
+    /// An odd amount of leds or columns gives the remaining one to the right channel
+    #[default]
+    Split,
+    /// Every channel is rendered on all leds. The outputs of the channels follow each other,
+    /// so they can drive separate led strips
+    Separate
+}
+
+impl StereoLayout {
+
+    /// Amount of leds of a row, which are rendered by the channel.
+    /// If the leds can't be split evenly, the last channel gets the remaining leds.
+    pub fn channel_width(&self, width: usize, channels: usize, channel: usize) -> usize {
+        let channels = channels.max(1);
+
+        match self {
+            StereoLayout::Split if channel + 1 == channels => width - width / channels * (channels - 1),
+            StereoLayout::Split => width / channels,
+            StereoLayout::Separate => width
+        }
+    }
+
+    /// Amount of led values of all channels
+    pub fn output_length(&self, n_led: usize, channels: usize) -> usize {
+        match self {
+            StereoLayout::Split => n_led,
+            StereoLayout::Separate => n_led * channels
+        }
+    }
+
+}
+
 
 impl BufferInfo {
 
-    /// Get the size of the buffer
+    /// Get the size of the buffer with all channels
     pub fn buffer_size(&self) -> usize {
-        return self.frame_length*self.frame_capture_size
+        self.window_length * self.channels
+    }
+
+    /// Amount of windows per second
+    pub fn frame_rate(&self) -> f32 {
+        self.sample_rate as f32 / self.hop_length as f32
     }
 
 }
 
 /// Contains all necessary information's to buffer audio data..
+/// Works like a sliding window over the incoming samples, like *samples_to_frames* in the notebook.
 pub struct AudioBuffer<T> {
     pub data: Vec<T>,
-    info: BufferInfo
+    info: BufferInfo,
+    // New interleaved samples since the last window
+    hop: Vec<T>,
+    // Next write position inside the hop
+    position: usize
 }
 
 impl AudioBuffer<i16> {
 
-    /// To buffer audio data, we need a *frame_length*, to know how long a single audio frame is.
-    /// In Addition, wee need to know how many frames are saved in the buffer.
-    pub fn new(frame_length: usize, frame_capture_size: usize) -> AudioBuffer<i16> {
-        AudioBuffer {
-            data: vec![0; frame_length*frame_capture_size],
-            info: BufferInfo { frame_length, frame_capture_size }
-        }
+    /// To buffer audio data, we need a *window_length*, to know how long a single window is.
+    /// In Addition, wee need to know after how many new samples the next window is created.
+    pub fn new(window_length: usize, hop_length: usize, sample_rate: u32) -> AudioBuffer<i16> {
+        AudioBuffer::from_info(BufferInfo { window_length, hop_length, sample_rate, channels: 1 })
     }
 
     pub fn from_info(info: BufferInfo) -> AudioBuffer<i16> {
         AudioBuffer {
-            data: vec![0; info.frame_length*info.frame_capture_size],
-            info
+            data: vec![0; info.buffer_size()],
+            hop: vec![0; info.hop_length * info.channels],
+            info,
+            position: 0
+        }
+    }
+
+    /// Write the interleaved samples behind the already buffered samples.
+    /// Every time *hop_length* new samples per channel were written, the window is moved
+    /// and the callback is called with the whole window.
+    /// Overlapping windows contain the samples of the previous window again.
+    pub fn push<I, C>(&mut self, samples: I, mut callback: C)
+        where
+            I: IntoIterator<Item = i16>,
+            C: FnMut(&[i16])
+    {
+        for sample in samples {
+            self.hop[self.position] = sample;
+            self.position += 1;
+
+            if self.position == self.hop.len() {
+                self.position = 0;
+                self.slide();
+                callback(self.data.as_slice());
+            }
+        }
+    }
+
+    /// Move the window of every channel by the hop
+    fn slide(&mut self) {
+        let window = self.info.window_length;
+        let hop = self.info.hop_length;
+        let channels = self.info.channels;
+
+        for (channel, data) in self.data.chunks_exact_mut(window).enumerate() {
+            // Samples of the channel inside the interleaved hop
+            let samples = self.hop.iter().skip(channel).step_by(channels);
+
+            if hop >= window {
+                // The window only contains the newest samples of the hop
+                for (value, sample) in data.iter_mut().zip(samples.skip(hop-window)) {
+                    *value = *sample;
+                }
+            } else {
+                data.copy_within(hop.., 0);
+                for (value, sample) in data[window-hop..].iter_mut().zip(samples) {
+                    *value = *sample;
+                }
+            }
         }
     }
 
@@ -72,12 +173,12 @@ impl<T> AudioBuffer<T>  {
         self.data.as_mut_slice()
     }
 
-    pub fn frame_length(&self) -> usize {
-        self.info.frame_length
+    pub fn window_length(&self) -> usize {
+        self.info.window_length
     }
 
-    pub fn frame_capture_size(&self) -> usize {
-        self.info.frame_capture_size
+    pub fn hop_length(&self) -> usize {
+        self.info.hop_length
     }
 
     pub fn buffer_size(&self) -> usize {
@@ -0,0 +1,104 @@
+use visualization_test::engine::output::{OutputConfig, OutputPipeline};
+use visualization_test::engine::output::correction::{ColorCorrection, Gamma, PowerBudget};
+use visualization_test::engine::output::pixel::{Color, PixelFormat, PixelFrame};
+
+const LEDS: usize = 10;
+
+
+#[test]
+fn test_gamma() {
+    let mut frame = PixelFrame::new(3);
+    frame.pixels_mut()[0] = Color::rgb(128, 128, 128);
+    frame.pixels_mut()[1] = Color::WHITE;
+    frame.pixels_mut()[2] = Color::new(u16::MAX / 4, u16::MAX / 2, 0);
+
+    // Linear correction doesn't change anything
+    let mut linear = frame.clone();
+    ColorCorrection::new(Gamma::LINEAR, 1.0, None).apply(&mut linear);
+    assert_eq!(linear, frame);
+
+    let gamma = Gamma { red: 2.0, green: 3.0, blue: 1.0, white: 1.0 };
+    ColorCorrection::new(gamma, 1.0, None).apply(&mut frame);
+
+    // Full and no brightness stay the same
+    assert_eq!(frame.pixels()[1], Color::WHITE);
+    assert_eq!(frame.pixels()[2].blue, 0);
+
+    // Every channel has its own table
+    let pixel = frame.pixels()[0];
+    assert!(pixel.red.abs_diff(u16::MAX / 4) < 300);
+    assert!(pixel.green.abs_diff(u16::MAX / 8) < 300);
+    assert_eq!(pixel.blue, Color::rgb(128, 128, 128).blue);
+    assert!(frame.pixels()[2].red.abs_diff(u16::MAX / 16) < 100);
+}
+
+#[test]
+fn test_brightness() {
+    let mut frame = PixelFrame::new(LEDS);
+    frame.fill(Color::rgb(200, 100, 0));
+
+    ColorCorrection::new(Gamma::LINEAR, 0.5, None).apply(&mut frame);
+    assert!(frame.pixels().iter().all(|pixel| *pixel == Color::rgb(100, 50, 0)));
+}
+
+#[test]
+fn test_power_budget() {
+    let budget = PowerBudget::new(200.0);
+    let mut frame = PixelFrame::new(LEDS);
+
+    // A black frame only needs the idle current
+    assert_eq!(budget.estimate(&frame), LEDS as f32);
+    assert_eq!(budget.scale_factor(&frame), 1.0);
+
+    // Full white needs 60 mA per led
+    frame.fill(Color::WHITE);
+    assert_eq!(budget.estimate(&frame), LEDS as f32 * 61.0);
+
+    ColorCorrection::new(Gamma::LINEAR, 1.0, Some(budget)).apply(&mut frame);
+    assert!((budget.estimate(&frame) - 200.0).abs() < 0.1);
+    assert!(frame.pixels().iter().all(|pixel| pixel.red == pixel.blue && pixel.red > 0));
+
+    // Frames inside the budget aren't changed
+    let mut frame = PixelFrame::new(LEDS);
+    frame.pixels_mut()[0] = Color::rgb(255, 0, 0);
+    let expected = frame.clone();
+    ColorCorrection::new(Gamma::LINEAR, 1.0, Some(budget)).apply(&mut frame);
+    assert_eq!(frame, expected);
+}
+
+#[test]
+fn test_pipeline() {
+    let mut frame = PixelFrame::new(2);
+    frame.fill(Color::rgb(255, 255, 255));
+
+    let config = OutputConfig {
+        format: PixelFormat::GRB,
+        gamma: Gamma::uniform(2.0),
+        brightness: 0.5,
+        ..Default::default()
+    };
+    assert!(config.validate().is_ok());
+
+    let mut bytes = vec![];
+    OutputPipeline::new(config, None).process(&frame, &mut bytes);
+    assert_eq!(bytes, vec![128; 6]);
+
+    // The input frame isn't changed by the pipeline
+    assert_eq!(frame.pixels()[0], Color::WHITE);
+}
+
+#[test]
+fn test_invalid_config() {
+    let invalid = [
+        OutputConfig { gamma: Gamma::uniform(0.0), ..Default::default() },
+        OutputConfig { gamma: Gamma { red: f32::NAN, ..Gamma::LINEAR }, ..Default::default() },
+        OutputConfig { brightness: 1.5, ..Default::default() },
+        OutputConfig { brightness: -0.1, ..Default::default() },
+        OutputConfig { power_budget: Some(PowerBudget::new(0.0)), ..Default::default() }
+    ];
+
+    for config in invalid {
+        assert!(config.validate().is_err(), "{:?}", config);
+    }
+    assert!(OutputConfig::default().validate().is_ok());
+}
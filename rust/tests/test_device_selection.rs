@@ -0,0 +1,93 @@
+use visualization_test::engine::Engine;
+use visualization_test::engine::errors::ApplicationError;
+use visualization_test::engine::input::{find_device, DeviceInfo, Playback};
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+use visualization_test::engine::input::store::DeviceStore;
+
+use anyhow::Result;
+
+const LEDS: usize = 60;
+
+
+fn device(position: usize, name: &str) -> DeviceInfo {
+    DeviceInfo {
+        position,
+        name: name.to_string(),
+        channels: 2,
+        sample_rate: 48000,
+        standard: false
+    }
+}
+
+fn engine() -> Engine {
+    let generator = GeneratorInputSource::new(Signal::Silence, 48000, Playback::RealTime);
+    Engine::new(Box::new(generator), LEDS)
+}
+
+fn is_not_found(result: Result<()>) -> bool {
+    matches!(
+        result.unwrap_err().downcast_ref::<ApplicationError>(),
+        Some(ApplicationError::DeviceNotFound(_))
+    )
+}
+
+#[test]
+fn test_find_device() {
+    let devices = vec![
+        device(0, "Microphone (Realtek High Definition Audio)"),
+        device(1, "Line In (Focusrite USB Audio)"),
+        device(2, "Focusrite"),
+        device(3, "Stereo Mix (Realtek High Definition Audio)")
+    ];
+
+    // Exact names win over partial names
+    assert_eq!(find_device(&devices, "focusrite").unwrap().position, 2);
+    // Substrings ignore the case
+    assert_eq!(find_device(&devices, "line in").unwrap().position, 1);
+    assert_eq!(find_device(&devices, "STEREO MIX").unwrap().position, 3);
+    // Every word must be part of the name, the first device wins
+    assert_eq!(find_device(&devices, "realtek audio").unwrap().position, 0);
+    assert_eq!(find_device(&devices, "mix realtek").unwrap().position, 3);
+
+    assert!(find_device(&devices, "Scarlett").is_none());
+    assert!(find_device(&devices, " ").is_none());
+}
+
+#[test]
+fn test_set_device_by_name() -> Result<()> {
+    let mut engine = engine();
+
+    engine.set_device_by_name("generator")?;
+    assert_eq!(engine.get_current_device_name()?, "Generator: Silence");
+
+    assert!(is_not_found(engine.set_device_by_name("Focusrite")));
+    assert!(is_not_found(engine.set_device(1)));
+    Ok(())
+}
+
+#[test]
+fn test_device_store() -> Result<()> {
+    let path = std::env::temp_dir().join("test_device_store").join("device.txt");
+    let _ = std::fs::remove_file(&path);
+
+    // Nothing is stored yet
+    let mut engine = engine();
+    engine.set_device_store(DeviceStore::new(&path))?;
+    assert_eq!(DeviceStore::new(&path).load()?, None);
+
+    // The selection is stored
+    engine.set_device_by_name("silence")?;
+    assert_eq!(DeviceStore::new(&path).load()?, Some(String::from("Generator: Silence")));
+
+    // A new engine selects the stored device again
+    let mut engine = self::engine();
+    engine.set_device_store(DeviceStore::new(&path))?;
+    assert_eq!(engine.get_current_device_name()?, "Generator: Silence");
+
+    // Unknown devices are skipped
+    DeviceStore::new(&path).save("Focusrite")?;
+    let mut engine = self::engine();
+    engine.set_device_store(DeviceStore::new(&path))?;
+    assert_eq!(engine.get_current_device_name()?, "Generator: Silence");
+    Ok(())
+}
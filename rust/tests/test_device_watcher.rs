@@ -0,0 +1,63 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use visualization_test::engine::events::DeviceEvent;
+use visualization_test::engine::input::DeviceInfo;
+use visualization_test::engine::input::watcher::{diff_devices, DeviceWatcher};
+
+use anyhow::Result;
+
+const TIMEOUT: Duration = Duration::from_secs(2);
+
+
+fn device(position: usize, name: &str, standard: bool) -> DeviceInfo {
+    DeviceInfo {
+        position,
+        name: name.to_string(),
+        channels: 2,
+        sample_rate: 48000,
+        standard
+    }
+}
+
+#[test]
+fn test_diff_devices() {
+    let old = vec![device(0, "Microphone", true), device(1, "Interface", false)];
+
+    // Only the positions changed
+    let moved = vec![device(0, "Interface", false), device(1, "Microphone", true)];
+    assert!(diff_devices(&old, &moved).is_empty());
+
+    let new = vec![device(0, "Microphone", false), device(1, "Interface", true), device(2, "Interface", false)];
+    assert_eq!(diff_devices(&old, &new), vec![
+        DeviceEvent::Added(device(2, "Interface", false)),
+        DeviceEvent::DefaultChanged(Some(device(1, "Interface", true)))
+    ]);
+
+    assert_eq!(diff_devices(&old, &[]), vec![
+        DeviceEvent::Removed(device(0, "Microphone", true)),
+        DeviceEvent::Removed(device(1, "Interface", false)),
+        DeviceEvent::DefaultChanged(None)
+    ]);
+}
+
+#[test]
+fn test_watcher() -> Result<()> {
+    let devices = Arc::new(Mutex::new(vec![device(0, "Microphone", true)]));
+    let clone = devices.clone();
+
+    let watcher = DeviceWatcher::with_lister(Duration::from_millis(10), move || Ok(clone.lock().unwrap().clone()));
+    let events = watcher.subscribe();
+
+    devices.lock().unwrap().push(device(1, "Interface", false));
+    assert_eq!(events.recv_timeout(TIMEOUT)?, DeviceEvent::Added(device(1, "Interface", false)));
+    assert_eq!(watcher.devices().len(), 2);
+
+    devices.lock().unwrap().remove(0);
+    assert_eq!(events.recv_timeout(TIMEOUT)?, DeviceEvent::Removed(device(0, "Microphone", true)));
+    assert_eq!(events.recv_timeout(TIMEOUT)?, DeviceEvent::DefaultChanged(None));
+
+    // The thread stops with the watcher
+    drop(watcher);
+    assert!(events.recv_timeout(TIMEOUT).is_err());
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use visualization_test::engine::output::{OutputConfig, OutputPipeline};
+use visualization_test::engine::output::dither::TemporalDither;
+use visualization_test::engine::output::pixel::{Color, PixelFormat, PixelFrame};
+
+const FRAMES: usize = 1000;
+
+
+#[test]
+fn test_average() {
+    let mut dither = TemporalDither::new();
+    dither.resize(1);
+
+    // The average of the frames matches the 16 bit value
+    for value in [0, 50, 257 / 2, 1000, 30000, u16::MAX - 20, u16::MAX] {
+        dither.reset();
+        let sum: u32 = (0..FRAMES).map(|_| dither.quantize(0, value) as u32).sum();
+        let average = sum as f32 / FRAMES as f32;
+
+        assert!((average - value as f32 / 257.0).abs() < 0.01, "{}", value);
+    }
+
+    // Exact 8 bit values are never changed
+    dither.reset();
+    assert!((0..FRAMES).all(|_| dither.quantize(0, 100 * 257) == 100));
+}
+
+#[test]
+fn test_channels() {
+    let mut dither = TemporalDither::new();
+    dither.resize(2);
+
+    // Every channel keeps its own error
+    let frames: Vec<(u8, u8)> = (0..4).map(|_| (dither.quantize(0, 64), dither.quantize(1, 257 * 3))).collect();
+    assert_eq!(frames, vec![(0, 3), (0, 3), (1, 3), (0, 3)]);
+
+    // Unknown channels are only rounded
+    assert_eq!(dither.quantize(5, 200), 1);
+}
+
+#[test]
+fn test_toggle() {
+    let mut frame = PixelFrame::new(4);
+    frame.fill(Color::new(300, 0, 0));
+
+    let average = |dithering: bool| {
+        let mut pipeline = OutputPipeline::new(OutputConfig { format: PixelFormat::RGB, dithering, ..Default::default() }, None);
+        let mut bytes = vec![];
+        let mut sum = 0;
+        for _ in 0..FRAMES {
+            pipeline.process(&frame, &mut bytes);
+            sum += bytes[0] as usize;
+            assert_eq!(bytes[1..3], [0, 0]);
+        }
+        sum as f32 / FRAMES as f32
+    };
+
+    // Without dithering the value is always rounded to 1
+    assert_eq!(average(false), 1.0);
+    assert!((average(true) - 300.0 / 257.0).abs() < 0.01);
+}
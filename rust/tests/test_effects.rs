@@ -0,0 +1,60 @@
+use visualization_test::engine::effects::EffectProcessing;
+use visualization_test::engine::effects::frequency::FrequencyEffect;
+use visualization_test::engine::effects::wave::{EnvelopeEffect, OscilloscopeEffect, WaveformScrollEffect};
+
+const LEDS: usize = 10;
+
+
+#[test]
+fn test_frequency_mirrored() {
+    let mut effect = FrequencyEffect;
+    let mel: Vec<i16> = (1..=effect.n_mel(LEDS) as i16).collect();
+    let mut output = [0; LEDS];
+
+    effect.process_frequency(&mel, &mut output);
+    assert_eq!(output, [5, 4, 3, 2, 1, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn test_oscilloscope() {
+    let mut effect = OscilloscopeEffect;
+    let wave: Vec<i16> = (0..100).map(|i| if i % 20 < 10 { -1000 } else { 500 }).collect();
+    let mut output = [0; LEDS];
+
+    effect.process_wave(&wave, &mut output);
+    assert_eq!(output, [1000, 500, 1000, 500, 1000, 500, 1000, 500, 1000, 500]);
+}
+
+#[test]
+fn test_waveform_scroll() {
+    let mut effect = WaveformScrollEffect;
+    let mut output = [0; LEDS];
+
+    effect.process_wave(&[100, -300, 200], &mut output);
+    effect.process_wave(&[50, -20], &mut output);
+
+    assert_eq!(output[..3], [50, 300, 0]);
+}
+
+#[test]
+fn test_envelope() {
+    let mut effect = EnvelopeEffect::new();
+    let mut output = [0; LEDS];
+    let lit = |output: &[i16]| output.iter().filter(|&&led| led > 0).count();
+
+    // Full scale square wave fills the strip from the middle
+    effect.process_wave(&[i16::MAX, -i16::MAX], &mut output);
+    let loud = lit(&output);
+    assert!(loud >= 6);
+    assert!(output[LEDS/2] > 0 && output[LEDS/2 - 1] > 0);
+
+    // Silence lets the envelope fall slowly
+    effect.process_wave(&[0; 4], &mut output);
+    let falling = lit(&output);
+    assert!(falling > 0 && falling <= loud);
+
+    for _ in 0..100 {
+        effect.process_wave(&[0; 4], &mut output);
+    }
+    assert_eq!(lit(&output), 0);
+}
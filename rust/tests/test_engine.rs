@@ -1,7 +1,18 @@
+use std::f32::consts::PI;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::thread::sleep;
 use std::time::Duration;
+use hound::{SampleFormat, WavSpec, WavWriter};
 use visualization_test::engine::Engine;
-use visualization_test::engine::input::DeviceInfo;
+use visualization_test::engine::errors::ApplicationError;
+use visualization_test::engine::filters::SimplePreEmphasisFilter;
+use visualization_test::engine::input::{ChannelStrategy, DataCallback, DeviceInfo, DeviceInputSource, ErrorCallback, InputConfig, InputSource, Playback};
+use visualization_test::engine::input::file::WavInputSource;
+use visualization_test::engine::output::CallbackSink;
+use visualization_test::engine::utils::StereoLayout;
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
 
 use anyhow::Result;
 const LEDS: usize = 60;
@@ -13,7 +24,7 @@ fn print_device(device: &DeviceInfo) {
 
 #[test]
 fn test_available_devices() -> Result<()> {
-    let engine = Engine::new(LEDS);
+    let engine = Engine::new(Box::new(DeviceInputSource::new()), LEDS);
     let devices = engine.get_available_devices();
 
     match devices {
@@ -32,7 +43,7 @@ fn test_available_devices() -> Result<()> {
 
 #[test]
 fn test_set_device() -> Result<()>{
-    let mut engine = Engine::new(LEDS);
+    let mut engine = Engine::new(Box::new(DeviceInputSource::new()), LEDS);
     let devices = engine.get_available_devices();
 
 
@@ -60,7 +71,7 @@ fn test_set_device() -> Result<()>{
 
 #[test]
 fn test_get_effects() -> () {
-    let engine = Engine::new(LEDS);
+    let engine = Engine::new(Box::new(DeviceInputSource::new()), LEDS);
 
     let effects = engine.get_effects();
 
@@ -71,14 +82,28 @@ fn test_get_effects() -> () {
 }
 
 #[test]
-fn test_set_effect() {
+fn test_set_effect() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::Sine { frequency: 440.0 }, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+    let (input, frames) = CountingInputSource::new(generator);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+
+    // Every effect processes the whole signal, no matter in which domain
+    for (i, _) in engine.get_effects().iter().enumerate() {
+        let before = frames.load(Ordering::SeqCst);
+        engine.set_effect(i)?;
+        assert_eq!(wait_for_frames(&frames, before + 50), before + 50);
+    }
 
+    assert!(engine.set_effect(engine.get_effects().len()).is_err());
+    Ok(())
 }
 
 
 #[test]
 fn test_get_filters() {
-    let engine = Engine::new(LEDS);
+    let engine = Engine::new(Box::new(DeviceInputSource::new()), LEDS);
 
     let filters = engine.get_filters();
 
@@ -88,34 +113,243 @@ fn test_get_filters() {
 }
 
 #[test]
-fn test_set_filter() {
+fn test_set_filter() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+    let (input, frames) = CountingInputSource::new(generator);
 
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    assert!(!engine.get_filters().is_empty());
 
+    engine.set_filtering(true);
+    assert!(engine.is_filtering_activated());
+    engine.set_filter(0)?;
+
+    assert_eq!(wait_for_frames(&frames, 50), 50);
+    Ok(())
 }
 
+#[test]
+fn test_frequency_range() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+
+    let mut engine = Engine::new(Box::new(generator), LEDS);
+    engine.set_frequency_range(100.0, 8000.0)?;
+    assert_eq!(engine.get_frequency_range(), (100.0, 8000.0));
+
+    for (min, max) in [(f32::NAN, 8000.0), (100.0, f32::NAN), (100.0, f32::INFINITY), (-1.0, 8000.0), (8000.0, 100.0)] {
+        let error = engine.set_frequency_range(min, max).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ApplicationError>(), Some(ApplicationError::InvalidFrequencyRange { .. })));
+    }
+
+    // The range is above the nyquist frequency, so the stream can't be built and the previous range stays
+    assert!(engine.set_frequency_range(30000.0, 40000.0).is_err());
+    assert_eq!(engine.get_frequency_range(), (100.0, 8000.0));
+    Ok(())
+}
+
+#[test]
+fn test_pre_emphasis() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+    let (input, frames) = CountingInputSource::new(generator);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    assert_eq!(engine.get_pre_emphasis(), SimplePreEmphasisFilter::DEFAULT_COEFFICIENT);
+    engine.set_filtering(true);
+
+    // The stream is rebuilt with the new coefficient
+    engine.set_pre_emphasis(0.97)?;
+    assert_eq!(engine.get_pre_emphasis(), 0.97);
+    assert_eq!(wait_for_frames(&frames, 50), 50);
+
+    for coefficient in [-0.1, 1.5, f32::NAN] {
+        let error = engine.set_pre_emphasis(coefficient).unwrap_err();
+        assert!(matches!(error.downcast_ref::<ApplicationError>(), Some(ApplicationError::InvalidPreEmphasis(_))));
+    }
+    assert_eq!(engine.get_pre_emphasis(), 0.97);
+    Ok(())
+}
+
+
+/// Input source which counts the frames of an underlying input source
+struct CountingInputSource<I: InputSource> {
+    inner: I,
+    frames: Arc<AtomicUsize>
+}
+
+impl<I: InputSource> CountingInputSource<I> {
+    fn new(inner: I) -> (Self, Arc<AtomicUsize>) {
+        let frames = Arc::new(AtomicUsize::new(0));
+        (CountingInputSource { inner, frames: frames.clone() }, frames)
+    }
+}
+
+impl<I: InputSource> InputSource for CountingInputSource<I> {
+    fn available_devices(&self) -> Result<Vec<DeviceInfo>> {
+        self.inner.available_devices()
+    }
+
+    fn set_device(&mut self, position: usize) -> Result<()> {
+        self.inner.set_device(position)
+    }
+
+    fn current_device_name(&self) -> Result<String> {
+        self.inner.current_device_name()
+    }
+
+    fn sample_rate(&self) -> Result<u32> {
+        self.inner.sample_rate()
+    }
+
+    fn build_stream(&mut self, config: &InputConfig, mut callback: DataCallback, error_callback: ErrorCallback) -> Result<()> {
+        let frames = self.frames.clone();
+        self.inner.build_stream(
+            config,
+            Box::new(move |data| {
+                callback(data);
+                frames.fetch_add(1, Ordering::SeqCst);
+            }),
+            error_callback
+        )
+    }
+
+    fn start_stream(&self) -> Result<()> {
+        self.inner.start_stream()
+    }
+
+    fn pause_stream(&self) -> Result<()> {
+        self.inner.pause_stream()
+    }
+}
+
+/// Wait until the expected amount of frames was processed or the timeout is reached
+fn wait_for_frames(frames: &AtomicUsize, expected: usize) -> usize {
+    for _ in 0..500 {
+        if frames.load(Ordering::SeqCst) >= expected { break }
+        sleep(Duration::from_millis(10));
+    }
+    frames.load(Ordering::SeqCst)
+}
 
 #[test]
 fn test_runtime() -> Result<()> {
-    let mut engine = Engine::new(LEDS);
+    let mut generator = GeneratorInputSource::new(Signal::Sine { frequency: 440.0 }, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+    let (input, frames) = CountingInputSource::new(generator);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
     engine.update_stream()?; //Start the stream!
 
-    sleep(Duration::from_secs(5));
+    // One second at 50 fps
+    assert_eq!(wait_for_frames(&frames, 50), 50);
     Ok(())
 }
 
 #[test]
 fn test_pause() -> Result<()> {
-    let mut engine = Engine::new(LEDS);
+    let generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::RealTime);
+    let (input, frames) = CountingInputSource::new(generator);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    assert!(engine.pause_stream().is_err());
     engine.update_stream()?;
 
-    sleep(Duration::from_secs(2));
+    assert!(wait_for_frames(&frames, 5) >= 5);
     engine.pause_stream()?;
-    sleep(Duration::from_secs(2));
+
+    // The chunk which was captured while pausing can still complete a frame
+    let paused = frames.load(Ordering::SeqCst);
+    sleep(Duration::from_millis(200));
+    assert!(frames.load(Ordering::SeqCst) <= paused + 1);
 
     Ok(())
 }
 
+#[test]
+fn test_frame_rates() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::Sine { frequency: 440.0 }, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+    let (input, frames) = CountingInputSource::new(generator);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    assert_eq!(engine.get_frame_rates(), (100, 50));
+
+    // One second at 30 fps
+    engine.set_frame_rates(60, 30)?;
+    assert_eq!(engine.get_frame_rates(), (60, 30));
+    assert_eq!(wait_for_frames(&frames, 30), 30);
+
+    // One second at 44 fps
+    engine.set_frame_rates(44, 44)?;
+    assert_eq!(wait_for_frames(&frames, 30 + 44), 30 + 44);
+
+    // The capture rate must be a multiple of the display rate
+    assert!(engine.set_frame_rates(100, 44).is_err());
+    assert!(engine.set_frame_rates(30, 60).is_err());
+    assert!(engine.set_frame_rates(0, 0).is_err());
+    assert_eq!(engine.get_frame_rates(), (44, 44));
+    Ok(())
+}
 
+/// Write one second with a loud tone on the left channel and silence on the right channel
+fn write_left_tone(name: &str) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(name);
+    let spec = WavSpec { channels: 2, sample_rate: 48000, bits_per_sample: 16, sample_format: SampleFormat::Int };
+    let mut writer = WavWriter::create(&path, spec)?;
 
+    for i in 0..48000 {
+        let phase = 2.0 * PI * 1000.0 * i as f32 / 48000.0;
+        writer.write_sample((phase.sin() * 20000.0) as i16)?;
+        writer.write_sample(0i16)?;
+    }
+    writer.finalize()?;
 
+    Ok(path)
+}
 
+/// Wait for new frames of the sink and return them
+fn collect_frames(frames: &Mutex<Vec<Vec<u8>>>, expected: usize) -> Vec<Vec<u8>> {
+    frames.lock().unwrap().clear();
+    for _ in 0..500 {
+        if frames.lock().unwrap().len() >= expected { break }
+        sleep(Duration::from_millis(10));
+    }
+    frames.lock().unwrap().clone()
+}
+
+#[test]
+fn test_stereo_layout() -> Result<()> {
+    let path = write_left_tone("engine_left_tone.wav")?;
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    let mut engine = Engine::new(Box::new(WavInputSource::open(&path, Playback::Unpaced)?), LEDS);
+    assert_eq!(engine.get_stereo_layout(), StereoLayout::Split);
+    assert_eq!(engine.get_output_length(), LEDS);
+    engine.set_sink(Box::new(CallbackSink::new(move |frame: &[u8]| clone.lock().unwrap().push(frame.to_vec()))))?;
+
+    // The left channel is shown on the first half, the silent right channel on the second half
+    engine.set_input_config(InputConfig { channels: ChannelStrategy::Stereo, ..Default::default() })?;
+    assert_eq!(engine.get_output_length(), LEDS);
+    let split = collect_frames(&frames, 10);
+    assert!(split.len() >= 10);
+    assert!(split.iter().all(|frame| frame.len() == LEDS * 3));
+    assert!(split.iter().any(|frame| frame[..LEDS / 2 * 3].iter().any(|&value| value > 0)));
+    assert!(split.iter().all(|frame| frame[LEDS / 2 * 3..].iter().all(|&value| value == 0)));
+
+    // Both channels are processed in every domain and have their own output
+    engine.set_stereo_layout(StereoLayout::Separate)?;
+    assert_eq!(engine.get_output_length(), 2 * LEDS);
+    for effect in [0, 1] {
+        engine.set_effect(effect)?;
+
+        let separate = collect_frames(&frames, 10);
+        assert!(separate.len() >= 10);
+        assert!(separate.iter().all(|frame| frame.len() == 2 * LEDS * 3));
+        assert!(separate.iter().any(|frame| frame[..LEDS * 3].iter().any(|&value| value > 0)));
+        assert!(separate.iter().all(|frame| frame[LEDS * 3..].iter().all(|&value| value == 0)));
+    }
+    Ok(())
+}
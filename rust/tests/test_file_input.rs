@@ -0,0 +1,176 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use visualization_test::engine::input::{ChannelStrategy, InputConfig, InputSample, InputSource, Playback};
+use visualization_test::engine::input::file::WavInputSource;
+
+use anyhow::Result;
+
+const SAMPLE_RATE: u32 = 48000;
+
+
+/// Write one second of a constant signal to a temporary wav file
+fn write_file(name: &str, channels: u16, bits: u16, format: SampleFormat) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(name);
+    let spec = WavSpec { channels, sample_rate: SAMPLE_RATE, bits_per_sample: bits, sample_format: format };
+    let mut writer = WavWriter::create(&path, spec)?;
+
+    for _ in 0..SAMPLE_RATE {
+        for _ in 0..channels {
+            match format {
+                SampleFormat::Float => writer.write_sample(0.5f32)?,
+                SampleFormat::Int => writer.write_sample(1 << (bits - 2))?
+            }
+        }
+    }
+    writer.finalize()?;
+
+    Ok(path)
+}
+
+/// Write one second, in which every channel has the value 1000 * (channel + 1)
+fn write_channels(name: &str, channels: u16) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(name);
+    let spec = WavSpec { channels, sample_rate: SAMPLE_RATE, bits_per_sample: 16, sample_format: SampleFormat::Int };
+    let mut writer = WavWriter::create(&path, spec)?;
+
+    for _ in 0..SAMPLE_RATE {
+        for channel in 0..channels {
+            writer.write_sample(1000 * (channel as i16 + 1))?;
+        }
+    }
+    writer.finalize()?;
+
+    Ok(path)
+}
+
+/// Play the whole file and collect all frames
+fn play(source: &mut WavInputSource) -> Result<Vec<Vec<i16>>> {
+    play_with(source, &InputConfig::default())
+}
+
+/// Play the whole file with the configuration and collect all frames
+fn play_with(source: &mut WavInputSource, config: &InputConfig) -> Result<Vec<Vec<i16>>> {
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    source.build_stream(
+        config,
+        Box::new(move |data| clone.lock().unwrap().push(data.to_vec())),
+        Box::new(|err| println!("Stream error: {}", err))
+    )?;
+    source.start_stream()?;
+    source.wait()?;
+
+    let frames = frames.lock().unwrap().clone();
+    Ok(frames)
+}
+
+#[test]
+fn test_unpaced_playback() -> Result<()> {
+    let path = write_file("test_unpaced_playback.wav", 2, 16, SampleFormat::Int)?;
+    let mut source = WavInputSource::open(&path, Playback::Unpaced)?;
+
+    let info = source.buffer_info(&InputConfig::default())?;
+    let frames = play(&mut source)?;
+
+    // One second at 50 fps
+    assert_eq!(frames.len(), 50);
+    assert!(frames.iter().all(|frame| frame.len() == info.buffer_size()));
+    assert!(frames.iter().flatten().all(|&sample| sample == 1 << 14));
+    Ok(())
+}
+
+#[test]
+fn test_real_time_playback() -> Result<()> {
+    let path = write_file("test_real_time_playback.wav", 1, 16, SampleFormat::Int)?;
+    let mut source = WavInputSource::open(&path, Playback::RealTime)?;
+
+    let start = Instant::now();
+    let frames = play(&mut source)?;
+
+    assert_eq!(frames.len(), 50);
+    assert!(start.elapsed() >= Duration::from_millis(950));
+    Ok(())
+}
+
+#[test]
+fn test_sample_formats() -> Result<()> {
+    let formats = [
+        (8, SampleFormat::Int),
+        (24, SampleFormat::Int),
+        (32, SampleFormat::Int),
+        (32, SampleFormat::Float)
+    ];
+
+    for (bits, format) in formats {
+        let path = write_file(&format!("test_sample_formats_{}_{:?}.wav", bits, format), 1, bits, format)?;
+        let mut source = WavInputSource::open(&path, Playback::Unpaced)?;
+
+        let frames = play(&mut source)?;
+        let sample = frames[0][0];
+        assert!((sample - i16::MAX/2).abs() <= 1, "{} bit {:?} decoded to {}", bits, format, sample);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_channel_strategies() -> Result<()> {
+    let path = write_channels("test_channel_strategies.wav", 4)?;
+    let mut source = WavInputSource::open(&path, Playback::Unpaced)?;
+    assert_eq!(source.len(), SAMPLE_RATE as usize);
+
+    let strategies = [
+        (ChannelStrategy::Channel(0), 1000),
+        (ChannelStrategy::Channel(2), 3000),
+        (ChannelStrategy::Average, 2500)
+    ];
+    for (strategy, expected) in strategies {
+        let config = InputConfig { channels: strategy, ..Default::default() };
+        let frames = play_with(&mut source, &config)?;
+
+        assert_eq!(frames.len(), 50);
+        assert!(frames.iter().all(|frame| frame.len() == 960));
+        assert!(frames.iter().flatten().all(|&sample| sample == expected), "{:?}", strategy);
+    }
+
+    // Stereo windows contain the left and then the right channel
+    let config = InputConfig { channels: ChannelStrategy::Stereo, ..Default::default() };
+    let info = source.buffer_info(&config)?;
+    let frames = play_with(&mut source, &config)?;
+    assert_eq!(info.buffer_size(), 2 * 960);
+    assert_eq!(frames.len(), 50);
+    assert!(frames.iter().all(|frame| frame[..960].iter().all(|&sample| sample == 1000)));
+    assert!(frames.iter().all(|frame| frame[960..].iter().all(|&sample| sample == 2000)));
+
+    // The file doesn't contain a fifth channel
+    let config = InputConfig { channels: ChannelStrategy::Channel(4), ..Default::default() };
+    assert!(play_with(&mut source, &config).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_mono_as_stereo() -> Result<()> {
+    let path = write_channels("test_mono_as_stereo.wav", 1)?;
+    let mut source = WavInputSource::open(&path, Playback::Unpaced)?;
+
+    let config = InputConfig { channels: ChannelStrategy::Stereo, ..Default::default() };
+    let frames = play_with(&mut source, &config)?;
+    assert_eq!(frames.len(), 50);
+    assert!(frames.iter().flatten().all(|&sample| sample == 1000));
+    Ok(())
+}
+
+#[test]
+fn test_sample_conversion() {
+    assert_eq!(1000i16.to_i16(), 1000);
+    assert_eq!(0u16.to_i16(), i16::MIN);
+    assert_eq!(32768u16.to_i16(), 0);
+    assert_eq!(u16::MAX.to_i16(), i16::MAX);
+    assert_eq!(i32::MAX.to_i16(), i16::MAX);
+    assert_eq!(i32::MIN.to_i16(), i16::MIN);
+    assert_eq!(1.0f32.to_i16(), i16::MAX);
+    assert_eq!((-2.0f32).to_i16(), -i16::MAX);
+    assert_eq!(0.0f32.to_i16(), 0);
+}
@@ -0,0 +1,37 @@
+use visualization_test::engine::filters::{FilterProcessing, SimplePreEmphasisFilter};
+
+
+#[test]
+fn test_pre_emphasis() {
+    let mut filter = SimplePreEmphasisFilter::new(0.5);
+    let mut data = [100, 100, 200, 0];
+
+    filter.process(&mut data);
+    assert_eq!(data, [100, 50, 150, -100]);
+}
+
+#[test]
+fn test_pre_emphasis_frame_boundaries() {
+    let signal: Vec<i16> = (0..100).map(|i| (i * 300 % 7000) as i16).collect();
+
+    let mut whole = signal.clone();
+    SimplePreEmphasisFilter::default().process(&mut whole);
+
+    // The state is carried over, so splitting the signal into frames changes nothing
+    let mut filter = SimplePreEmphasisFilter::default();
+    let mut frames = signal.clone();
+    for frame in frames.chunks_mut(30) {
+        filter.process(frame);
+    }
+
+    assert_eq!(whole, frames);
+}
+
+#[test]
+fn test_pre_emphasis_saturation() {
+    let mut filter = SimplePreEmphasisFilter::new(1.0);
+    let mut data = [i16::MAX, i16::MIN, i16::MAX];
+
+    filter.process(&mut data);
+    assert_eq!(data, [i16::MAX, i16::MIN, i16::MAX]);
+}
@@ -0,0 +1,196 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use visualization_test::engine::input::{InputConfig, InputSource, Playback};
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+
+use anyhow::Result;
+
+const SAMPLE_RATE: u32 = 48000;
+
+
+/// Generate one second of the signal and collect all windows
+fn generate_windows(signal: Signal, config: &InputConfig) -> Result<Vec<Vec<i16>>> {
+    let mut source = GeneratorInputSource::new(signal, SAMPLE_RATE, Playback::Unpaced);
+    source.set_duration(Some(Duration::from_secs(1)));
+
+    let windows = Arc::new(Mutex::new(vec![]));
+    let clone = windows.clone();
+
+    source.build_stream(
+        config,
+        Box::new(move |data| clone.lock().unwrap().push(data.to_vec())),
+        Box::new(|err| println!("Stream error: {}", err))
+    )?;
+    source.start_stream()?;
+    source.wait()?;
+
+    let windows = windows.lock().unwrap().clone();
+    Ok(windows)
+}
+
+/// Generate one second of the signal and collect all samples.
+/// The default windows don't overlap, so they can be joined together.
+fn generate(signal: Signal) -> Result<Vec<i16>> {
+    let windows = generate_windows(signal, &InputConfig::default())?;
+    Ok(windows.concat())
+}
+
+/// Count how often the signal crosses zero from negative to positive
+fn rising_zero_crossings(samples: &[i16]) -> usize {
+    samples.windows(2).filter(|pair| pair[0] < 0 && pair[1] >= 0).count()
+}
+
+#[test]
+fn test_geometry() -> Result<()> {
+    let source = GeneratorInputSource::new(Signal::Silence, SAMPLE_RATE, Playback::Unpaced);
+    let info = source.buffer_info(&InputConfig::default())?;
+
+    // Same geometry as an audio device with 48kHz
+    assert_eq!(info.window_length, 960);
+    assert_eq!(info.hop_length, 960);
+    assert_eq!(info.frame_rate(), 50.0);
+    assert_eq!(source.available_devices()?.len(), 1);
+
+    let config = InputConfig { window_length: Some(2048), hop_length: Some(512), ..Default::default() };
+    let info = source.buffer_info(&config)?;
+    assert_eq!(info.buffer_size(), 2048);
+    assert_eq!(info.frame_rate(), 93.75);
+
+    let config = InputConfig { window_length: None, hop_length: Some(0), ..Default::default() };
+    assert!(source.buffer_info(&config).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_sliding_window() -> Result<()> {
+    let config = InputConfig { window_length: Some(1024), hop_length: Some(256), ..Default::default() };
+    let windows = generate_windows(Signal::WhiteNoise, &config)?;
+
+    // A window is created after every hop, the first windows are padded with zeros at their start
+    assert_eq!(windows.len(), SAMPLE_RATE as usize / 256);
+    assert!(windows.iter().all(|window| window.len() == 1024));
+    assert!(windows[0][..768].iter().all(|&sample| sample == 0));
+    assert!(windows[0][768..].iter().any(|&sample| sample != 0));
+
+    // Every window contains the newest hop and the last samples of the previous window
+    for pair in windows.windows(2) {
+        assert_eq!(pair[0][256..], pair[1][..768]);
+    }
+
+    // Hops larger than the window skip samples
+    let config = InputConfig { window_length: Some(480), hop_length: Some(960), ..Default::default() };
+    let windows = generate_windows(Signal::WhiteNoise, &config)?;
+    assert_eq!(windows.len(), 50);
+    assert!(windows.iter().all(|window| window.len() == 480));
+    Ok(())
+}
+
+#[test]
+fn test_sine() -> Result<()> {
+    let samples = generate(Signal::Sine { frequency: 100.0 })?;
+
+    assert_eq!(samples.len(), SAMPLE_RATE as usize);
+    assert_eq!(rising_zero_crossings(&samples), 99);
+    assert!(samples.iter().any(|&sample| sample > i16::MAX - 10));
+    Ok(())
+}
+
+#[test]
+fn test_chirp() -> Result<()> {
+    let samples = generate(Signal::Chirp { start: 100.0, end: 1000.0, duration: Duration::from_secs(1) })?;
+    let half = samples.len() / 2;
+
+    // The second half of the sweep has a higher frequency
+    assert!(rising_zero_crossings(&samples[half..]) > 2 * rising_zero_crossings(&samples[..half]));
+    Ok(())
+}
+
+/// Mean difference between two samples relative to the rms of the signal
+fn roughness(samples: &[i16]) -> f64 {
+    let rms = (samples.iter().map(|&sample| (sample as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt();
+    let difference = samples.windows(2)
+        .map(|pair| (pair[1] as f64 - pair[0] as f64).abs())
+        .sum::<f64>() / samples.len() as f64;
+
+    difference / rms
+}
+
+#[test]
+fn test_noise() -> Result<()> {
+    let white = generate(Signal::WhiteNoise)?;
+    let mean = white.iter().map(|&sample| sample as f64).sum::<f64>() / white.len() as f64;
+    assert!(mean.abs() < 1000.0, "White noise has a mean of {}", mean);
+
+    // Pink noise has more power at low frequencies, so it changes slower
+    let pink = generate(Signal::PinkNoise)?;
+    assert!(pink.iter().any(|&sample| sample != 0));
+    assert!(roughness(&pink) < roughness(&white) / 2.0);
+    Ok(())
+}
+
+#[test]
+fn test_impulses_and_clicks() -> Result<()> {
+    let impulses = generate(Signal::ImpulseTrain { frequency: 10.0 })?;
+    assert_eq!(impulses.iter().filter(|&&sample| sample == i16::MAX).count(), 10);
+
+    // 120 bpm are 2 clicks per second, which start at 0s and 0.5s
+    let clicks = generate(Signal::ClickTrack { bpm: 120.0 })?;
+    assert!(clicks[..1000].iter().any(|&sample| sample != 0));
+    assert!(clicks[1000..24000].iter().all(|&sample| sample == 0));
+    assert!(clicks[24000..25000].iter().any(|&sample| sample != 0));
+
+    let silence = generate(Signal::Silence)?;
+    assert!(silence.iter().all(|&sample| sample == 0));
+    Ok(())
+}
+
+#[test]
+fn test_resampling() -> Result<()> {
+    // Sources with different sample rates result in the same windows
+    for source_rate in [22050, 44100, 96000] {
+        let mut source = GeneratorInputSource::new(Signal::Sine { frequency: 100.0 }, source_rate, Playback::Unpaced);
+        source.set_duration(Some(Duration::from_secs(1)));
+
+        let config = InputConfig { sample_rate: Some(SAMPLE_RATE), ..Default::default() };
+        let info = source.buffer_info(&config)?;
+        assert_eq!(info.sample_rate, SAMPLE_RATE);
+        assert_eq!(info.buffer_size(), 960);
+
+        let windows = Arc::new(Mutex::new(vec![]));
+        let clone = windows.clone();
+        source.build_stream(
+            &config,
+            Box::new(move |data| clone.lock().unwrap().push(data.to_vec())),
+            Box::new(|err| println!("Stream error: {}", err))
+        )?;
+        source.start_stream()?;
+        source.wait()?;
+
+        // About one second at the internal rate, which still contains 100 periods per second
+        let samples = windows.lock().unwrap().concat();
+        let periods = samples.len() * 100 / SAMPLE_RATE as usize;
+        assert!(samples.len().abs_diff(SAMPLE_RATE as usize) <= 960, "{} Hz: {} samples", source_rate, samples.len());
+        assert!(rising_zero_crossings(&samples).abs_diff(periods) <= 1, "{} Hz", source_rate);
+    }
+
+    let config = InputConfig { sample_rate: Some(0), ..Default::default() };
+    assert!(config.validate().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_anti_aliasing() -> Result<()> {
+    // Peak of the sine after the filter has settled, resampled from 48 kHz to 16 kHz
+    let peak = |frequency: f32| -> Result<i16> {
+        let config = InputConfig { sample_rate: Some(16000), ..Default::default() };
+        let samples = generate_windows(Signal::Sine { frequency }, &config)?.concat();
+        Ok(samples[1000..].iter().map(|sample| sample.saturating_abs()).max().unwrap_or(0))
+    };
+
+    // Frequencies below the new nyquist frequency pass, higher frequencies don't appear as aliases
+    let passed = peak(1000.0)?;
+    let aliased = peak(20000.0)?;
+    assert!(passed > i16::MAX / 4, "{}", passed);
+    assert!(aliased < passed / 20, "{} {}", passed, aliased);
+    Ok(())
+}
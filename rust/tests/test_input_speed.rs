@@ -1,5 +1,5 @@
 use std::time::Instant;
-use visualization_test::engine::input::DeviceInputSource;
+use visualization_test::engine::input::{DeviceInputSource, InputConfig, InputSource};
 
 #[test]
 fn test_input_speed() {
@@ -9,6 +9,7 @@ fn test_input_speed() {
     let mut count = 0;
 
     input_source.build_mono_stream(
+        &InputConfig::default(),
         move |data, info| {
 
             // Count to 50 and print the current time
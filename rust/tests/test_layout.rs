@@ -0,0 +1,162 @@
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+use visualization_test::engine::Engine;
+use visualization_test::engine::errors::ApplicationError;
+use visualization_test::engine::input::Playback;
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+use visualization_test::engine::output::CallbackSink;
+use visualization_test::engine::output::layout::{LedLayout, Segment, Wiring};
+use visualization_test::engine::output::pixel::{Color, PixelFrame};
+use visualization_test::engine::utils::StereoLayout;
+
+use anyhow::Result;
+
+
+/// Canvas indices of all physical leds
+fn indices(layout: &LedLayout) -> Vec<Option<usize>> {
+    (0..layout.physical_length()).map(|i| layout.canvas_index(i)).collect()
+}
+
+#[test]
+fn test_segments() -> Result<()> {
+    let layout = LedLayout::new(6, 1, vec![
+        Segment::Strip { start: 0, length: 3, reversed: false },
+        Segment::Gap { length: 2 },
+        Segment::Strip { start: 3, length: 3, reversed: true },
+        Segment::Strip { start: 1, length: 1, reversed: false }
+    ])?;
+
+    assert_eq!(layout.canvas_length(), 6);
+    assert_eq!(layout.physical_length(), 9);
+    assert_eq!(indices(&layout), vec![
+        Some(0), Some(1), Some(2), None, None, Some(5), Some(4), Some(3), Some(1)
+    ]);
+    assert_eq!(layout.segments().iter().map(|segment| segment.length()).sum::<usize>(), 9);
+
+    assert_eq!(indices(&LedLayout::strip(3)?), vec![Some(0), Some(1), Some(2)]);
+    Ok(())
+}
+
+#[test]
+fn test_matrix_wiring() -> Result<()> {
+    let wired = |wiring| -> Result<Vec<Option<usize>>> {
+        Ok(indices(&LedLayout::matrix(3, 2, wiring)?))
+    };
+
+    // Canvas:
+    // 0 1 2
+    // 3 4 5
+    assert_eq!(wired(Wiring::Rows)?, [0, 1, 2, 3, 4, 5].map(Some));
+    assert_eq!(wired(Wiring::SerpentineRows)?, [0, 1, 2, 5, 4, 3].map(Some));
+    assert_eq!(wired(Wiring::Columns)?, [0, 3, 1, 4, 2, 5].map(Some));
+    assert_eq!(wired(Wiring::SerpentineColumns)?, [0, 3, 4, 1, 2, 5].map(Some));
+
+    // Two panels side by side on one canvas
+    let layout = LedLayout::new(4, 2, vec![
+        Segment::Matrix { x: 0, y: 0, width: 2, height: 2, wiring: Wiring::SerpentineRows },
+        Segment::Matrix { x: 2, y: 0, width: 2, height: 2, wiring: Wiring::Rows }
+    ])?;
+    assert_eq!(indices(&layout), [0, 1, 5, 4, 2, 3, 6, 7].map(Some));
+    Ok(())
+}
+
+#[test]
+fn test_invalid_layouts() {
+    assert!(LedLayout::new(0, 5, vec![]).is_err());
+    assert!(LedLayout::new(5, 1, vec![Segment::Strip { start: 3, length: 3, reversed: false }]).is_err());
+    assert!(LedLayout::new(4, 4, vec![
+        Segment::Matrix { x: 2, y: 1, width: 3, height: 2, wiring: Wiring::Rows }
+    ]).is_err());
+}
+
+#[test]
+fn test_overflowing_layouts() {
+    assert!(LedLayout::new(usize::MAX, 2, vec![]).is_err());
+    assert!(LedLayout::new(5, 1, vec![Segment::Strip { start: usize::MAX, length: 2, reversed: false }]).is_err());
+    assert!(LedLayout::new(4, 4, vec![
+        Segment::Matrix { x: usize::MAX, y: 0, width: 2, height: 2, wiring: Wiring::Rows }
+    ]).is_err());
+    assert!(LedLayout::new(4, 4, vec![
+        Segment::Matrix { x: 0, y: 1, width: 2, height: usize::MAX, wiring: Wiring::Rows }
+    ]).is_err());
+}
+
+#[test]
+fn test_map() -> Result<()> {
+    let layout = LedLayout::new(3, 1, vec![
+        Segment::Strip { start: 0, length: 3, reversed: true },
+        Segment::Gap { length: 1 }
+    ])?;
+
+    let mut canvas = PixelFrame::new(3);
+    canvas.pixels_mut()[0] = Color::rgb(255, 0, 0);
+    canvas.pixels_mut()[2] = Color::rgb(0, 0, 255);
+
+    let mut physical = PixelFrame::new(1);
+    layout.map(&canvas, &mut physical);
+    assert_eq!(physical.pixels(), &[Color::rgb(0, 0, 255), Color::BLACK, Color::rgb(255, 0, 0), Color::BLACK]);
+
+    // Missing pixels of the canvas stay dark
+    layout.map(&PixelFrame::new(1), &mut physical);
+    assert!(physical.pixels().iter().all(|pixel| *pixel == Color::BLACK));
+    Ok(())
+}
+
+#[test]
+fn test_engine_layout() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_millis(200)));
+
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    let mut engine = Engine::new(Box::new(generator), 10);
+    assert_eq!(engine.get_pixel_count(), 10);
+
+    let layout = LedLayout::new(8, 1, vec![
+        Segment::Strip { start: 0, length: 4, reversed: false },
+        Segment::Gap { length: 3 },
+        Segment::Strip { start: 4, length: 4, reversed: true }
+    ])?;
+    engine.set_sink(Box::new(CallbackSink::new(move |frame: &[u8]| clone.lock().unwrap().push(frame.to_vec()))))?;
+    engine.set_led_layout(Some(layout.clone()))?;
+
+    assert_eq!(engine.get_led_layout(), Some(&layout));
+    assert_eq!(engine.get_output_length(), 8);
+    assert_eq!(engine.get_pixel_count(), 11);
+
+    for _ in 0..500 {
+        if frames.lock().unwrap().len() >= 10 { break }
+        sleep(Duration::from_millis(10));
+    }
+    let frames = frames.lock().unwrap();
+    assert!(frames.len() >= 10);
+    assert!(frames.iter().all(|frame| frame.len() == 11 * 3));
+    assert!(frames.iter().all(|frame| frame[12..21].iter().all(|&value| value == 0)));
+
+    // The mirrored frequency effect is symmetric, so the reversed strip repeats the first one
+    for frame in frames.iter() {
+        assert_eq!(frame[..12], frame[21..]);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_separate_channels() -> Result<()> {
+    let generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    let mut engine = Engine::new(Box::new(generator), 10);
+
+    // The layout only covers the canvas of one channel
+    engine.set_stereo_layout(StereoLayout::Separate)?;
+    let error = engine.set_led_layout(Some(LedLayout::strip(10)?)).unwrap_err();
+    assert!(matches!(error.downcast_ref::<ApplicationError>(), Some(ApplicationError::InvalidLayout(_))));
+    assert_eq!(engine.get_led_layout(), None);
+
+    engine.set_stereo_layout(StereoLayout::Split)?;
+    engine.set_led_layout(Some(LedLayout::strip(10)?))?;
+    let error = engine.set_stereo_layout(StereoLayout::Separate).unwrap_err();
+    assert!(matches!(error.downcast_ref::<ApplicationError>(), Some(ApplicationError::InvalidLayout(_))));
+    assert_eq!(engine.get_stereo_layout(), StereoLayout::Split);
+    Ok(())
+}
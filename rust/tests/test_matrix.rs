@@ -0,0 +1,218 @@
+use std::f32::consts::PI;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use visualization_test::engine::Engine;
+use visualization_test::engine::effects::canvas::{Canvas, CanvasProcessing};
+use visualization_test::engine::effects::matrix::{RadialSpectrumEffect, SpectrogramEffect, SpectrumBarsEffect};
+use visualization_test::engine::input::{ChannelStrategy, InputConfig, Playback};
+use visualization_test::engine::input::file::WavInputSource;
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+use visualization_test::engine::output::CallbackSink;
+use visualization_test::engine::output::layout::{LedLayout, Wiring};
+use visualization_test::engine::output::palette::{ColorMapping, ColorScheme, Palette};
+use visualization_test::engine::output::pixel::Color;
+use visualization_test::engine::utils::StereoLayout;
+
+use anyhow::Result;
+
+const SAMPLE_RATE: u32 = 48000;
+
+const WIDTH: usize = 4;
+const HEIGHT: usize = 4;
+const FULL: i16 = i16::MAX;
+
+
+#[test]
+fn test_canvas() {
+    let mut pixels: Vec<i16> = (0..14).collect();
+    let mut canvas = Canvas::new(WIDTH, &mut pixels);
+
+    // The incomplete last row is ignored
+    assert_eq!((canvas.width(), canvas.height()), (4, 3));
+    assert_eq!(canvas.get(1, 2), 9);
+
+    canvas.set(3, 0, -1);
+    canvas.scroll_down();
+    assert_eq!(canvas.row_mut(0), &[0, 1, 2, -1]);
+    assert_eq!(canvas.row_mut(2), &[4, 5, 6, 7]);
+    assert_eq!(pixels[12..], [12, 13]);
+}
+
+#[test]
+fn test_spectrum_bars() {
+    let mut effect = SpectrumBarsEffect;
+    let mut pixels = vec![0; WIDTH * HEIGHT];
+
+    assert_eq!(effect.n_mel(WIDTH, HEIGHT), WIDTH);
+    effect.process_frequency(&[0, FULL / 4, FULL / 2, FULL], &mut Canvas::new(WIDTH, &mut pixels));
+
+    // The bars grow from the bottom
+    let half = FULL / 2;
+    assert_eq!(pixels, vec![
+        0, 0,        0,    FULL,
+        0, 0,        0,    FULL,
+        0, 0,        half, FULL,
+        0, FULL / 4, half, FULL
+    ]);
+
+    // The colour follows the height
+    let scheme = ColorScheme::new(Palette::RAINBOW, ColorMapping::Position);
+    let mut colors = vec![Color::BLACK; WIDTH * HEIGHT];
+    effect.colorize(&[FULL; WIDTH * HEIGHT], WIDTH, &scheme, &mut colors);
+    assert_eq!(colors[12], Palette::RAINBOW.color_at(0.0));
+    assert_eq!(colors[0], Palette::RAINBOW.color_at(1.0));
+    assert_eq!(colors[13], colors[15]);
+}
+
+#[test]
+fn test_spectrogram() {
+    let mut effect = SpectrogramEffect;
+    let mut pixels = vec![0; WIDTH * HEIGHT];
+
+    for frame in 1..=3 {
+        effect.process_frequency(&[frame; WIDTH], &mut Canvas::new(WIDTH, &mut pixels));
+    }
+
+    // The newest spectrum is on the top
+    assert_eq!(pixels, vec![3, 3, 3, 3, 2, 2, 2, 2, 1, 1, 1, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn test_radial_spectrum() {
+    let mut effect = RadialSpectrumEffect;
+    let mut pixels = vec![0; WIDTH * HEIGHT];
+
+    let n_mel = effect.n_mel(WIDTH, HEIGHT);
+    assert_eq!(n_mel, 3);
+    effect.process_frequency(&[10, 20, 30], &mut Canvas::new(WIDTH, &mut pixels));
+
+    // Rings around the centre
+    assert_eq!(pixels, vec![
+        30, 20, 20, 30,
+        20, 10, 10, 20,
+        20, 10, 10, 20,
+        30, 20, 20, 30
+    ]);
+}
+
+#[test]
+fn test_engine_matrix() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(2)));
+
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    let mut engine = Engine::new(Box::new(generator), 10);
+    engine.set_sink(Box::new(CallbackSink::new(move |frame: &[u8]| clone.lock().unwrap().push(frame.to_vec()))))?;
+    engine.set_led_layout(Some(LedLayout::matrix(32, 8, Wiring::SerpentineColumns)?))?;
+
+    let canvas_effects: Vec<usize> = engine.get_effects().iter().enumerate()
+        .filter(|(_, effect)| effect.canvas)
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(canvas_effects.len(), 3);
+
+    for effect in canvas_effects {
+        frames.lock().unwrap().clear();
+        engine.set_effect(effect)?;
+
+        for _ in 0..500 {
+            if frames.lock().unwrap().len() >= 5 { break }
+            sleep(Duration::from_millis(10));
+        }
+        let frames = frames.lock().unwrap();
+        assert!(frames.len() >= 5);
+        assert!(frames.iter().all(|frame| frame.len() == 32 * 8 * 3));
+        assert!(frames.iter().any(|frame| frame.iter().any(|&value| value > 0)));
+    }
+    Ok(())
+}
+
+/// Write one second with a loud tone on the left channel and silence on the right channel
+fn write_left_tone(name: &str) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(name);
+    let spec = WavSpec { channels: 2, sample_rate: SAMPLE_RATE, bits_per_sample: 16, sample_format: SampleFormat::Int };
+    let mut writer = WavWriter::create(&path, spec)?;
+
+    for i in 0..SAMPLE_RATE {
+        let phase = 2.0 * PI * 1000.0 * i as f32 / SAMPLE_RATE as f32;
+        writer.write_sample((phase.sin() * 20000.0) as i16)?;
+        writer.write_sample(0i16)?;
+    }
+    writer.finalize()?;
+
+    Ok(path)
+}
+
+#[test]
+fn test_stereo_matrix() -> Result<()> {
+    let path = write_left_tone("matrix_left_tone.wav")?;
+    let source = WavInputSource::open(&path, Playback::RealTime)?;
+
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    // The height is odd, so splitting the rows would leave a row without a channel
+    let (width, height) = (6, 5);
+    let mut engine = Engine::new(Box::new(source), 10);
+    engine.set_led_layout(Some(LedLayout::matrix(width, height, Wiring::Rows)?))?;
+    engine.set_input_config(InputConfig { channels: ChannelStrategy::Stereo, ..Default::default() })?;
+    engine.set_effect(engine.get_effects().iter().position(|effect| effect.canvas).unwrap())?;
+
+    // The sink is set last, so it doesn't receive frames of the previous streams
+    engine.set_sink(Box::new(CallbackSink::new(move |frame: &[u8]| clone.lock().unwrap().push(frame.to_vec()))))?;
+
+    for _ in 0..500 {
+        if frames.lock().unwrap().len() >= 10 { break }
+        sleep(Duration::from_millis(10));
+    }
+    let frames = frames.lock().unwrap();
+    assert!(frames.len() >= 10);
+
+    // The left channel is shown on the left columns of every row, the silent right channel on the right columns
+    let row = |frame: &[u8], y: usize| frame[y * width * 3..(y + 1) * width * 3].to_vec();
+    for frame in frames.iter() {
+        for y in 0..height {
+            assert!(row(frame, y)[width / 2 * 3..].iter().all(|&value| value == 0));
+        }
+    }
+    assert!(frames.iter().any(|frame| row(frame, height - 1)[..width / 2 * 3].iter().any(|&value| value > 0)));
+    Ok(())
+}
+
+#[test]
+fn test_odd_stereo_matrix() -> Result<()> {
+    // The right channel gets the remaining column
+    assert_eq!(StereoLayout::Split.channel_width(5, 2, 0), 2);
+    assert_eq!(StereoLayout::Split.channel_width(5, 2, 1), 3);
+    assert_eq!(StereoLayout::Separate.channel_width(5, 2, 1), 5);
+
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::RealTime);
+    generator.set_duration(Some(Duration::from_secs(1)));
+
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    let size = 5;
+    let mut engine = Engine::new(Box::new(generator), 10);
+    engine.set_led_layout(Some(LedLayout::matrix(size, size, Wiring::Rows)?))?;
+    engine.set_input_config(InputConfig { channels: ChannelStrategy::Stereo, ..Default::default() })?;
+    engine.set_effect(engine.get_effects().iter().position(|effect| effect.canvas).unwrap())?;
+    engine.set_sink(Box::new(CallbackSink::new(move |frame: &[u8]| clone.lock().unwrap().push(frame.to_vec()))))?;
+
+    for _ in 0..500 {
+        if frames.lock().unwrap().len() >= 10 { break }
+        sleep(Duration::from_millis(10));
+    }
+    let frames = frames.lock().unwrap();
+    assert!(frames.len() >= 10);
+
+    // The bars of the noise reach the last column of the bottom row
+    let last = ((size * size) - 1) * 3;
+    assert!(frames.iter().any(|frame| frame[last..last + 3].iter().any(|&value| value > 0)));
+    Ok(())
+}
@@ -0,0 +1,165 @@
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+use visualization_test::engine::Engine;
+use visualization_test::engine::input::Playback;
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+use visualization_test::engine::output::{CallbackSink, OutputConfig, OutputSink};
+use visualization_test::engine::output::pixel::PixelFormat;
+
+use anyhow::Result;
+
+const LEDS: usize = 60;
+
+
+/// Everything the engine did with the sink
+#[derive(Default)]
+struct Record {
+    starts: Vec<u32>,
+    frames: Vec<Vec<u8>>,
+    stops: usize
+}
+
+struct RecordingSink {
+    record: Arc<Mutex<Record>>
+}
+
+impl OutputSink for RecordingSink {
+    fn start(&mut self, frame_rate: u32) -> Result<()> {
+        self.record.lock().unwrap().starts.push(frame_rate);
+        Ok(())
+    }
+
+    fn write(&mut self, frame: &[u8]) -> Result<()> {
+        self.record.lock().unwrap().frames.push(frame.to_vec());
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        self.record.lock().unwrap().stops += 1;
+        Ok(())
+    }
+}
+
+/// Wait until the sink received the expected amount of frames
+fn wait_for_frames(record: &Mutex<Record>, expected: usize) -> usize {
+    for _ in 0..500 {
+        if record.lock().unwrap().frames.len() >= expected { break }
+        sleep(Duration::from_millis(10));
+    }
+    record.lock().unwrap().frames.len()
+}
+
+#[test]
+fn test_sink_lifecycle() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::Sine { frequency: 440.0 }, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+
+    let record = Arc::new(Mutex::new(Record::default()));
+    let mut engine = Engine::new(Box::new(generator), LEDS);
+    engine.set_sink(Box::new(RecordingSink { record: record.clone() }))?;
+
+    // Every frame of the stream reaches the sink
+    engine.update_stream()?;
+    assert_eq!(wait_for_frames(&record, 50), 50);
+    {
+        let record = record.lock().unwrap();
+        assert_eq!(record.starts, vec![50]);
+        assert!(record.frames.iter().all(|frame| frame.len() == LEDS * 3));
+        assert!(record.frames.iter().any(|frame| frame.iter().any(|&value| value > 0)));
+    }
+
+    engine.pause_stream()?;
+    assert_eq!(record.lock().unwrap().stops, 1);
+
+    // The removed sink doesn't receive anything anymore
+    assert!(engine.remove_sink()?.is_some());
+    engine.update_stream()?;
+    sleep(Duration::from_millis(100));
+    assert_eq!(record.lock().unwrap().frames.len(), 50);
+    assert_eq!(record.lock().unwrap().starts.len(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_callback_sink() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_secs(1)));
+
+    let frames = Arc::new(Mutex::new(0));
+    let clone = frames.clone();
+    let stops = Arc::new(Mutex::new(0));
+    let stop_clone = stops.clone();
+
+    let sink = CallbackSink::new(move |_: &[u8]| *clone.lock().unwrap() += 1)
+        .with_stop(move || *stop_clone.lock().unwrap() += 1);
+    let mut engine = Engine::new(Box::new(generator), LEDS);
+    engine.set_sink(Box::new(sink))?;
+    engine.set_frame_rates(60, 30)?;
+
+    for _ in 0..500 {
+        if *frames.lock().unwrap() >= 30 { break }
+        sleep(Duration::from_millis(10));
+    }
+    assert_eq!(*frames.lock().unwrap(), 30);
+
+    // Dropping the engine stops the sink
+    drop(engine);
+    assert_eq!(*stops.lock().unwrap(), 1);
+    Ok(())
+}
+
+#[test]
+fn test_pixel_format() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::Sine { frequency: 440.0 }, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_millis(200)));
+
+    let record = Arc::new(Mutex::new(Record::default()));
+    let mut engine = Engine::new(Box::new(generator), LEDS);
+    engine.set_sink(Box::new(RecordingSink { record: record.clone() }))?;
+    engine.set_output_config(OutputConfig { format: PixelFormat::RGBW, ..Default::default() })?;
+
+    assert_eq!(wait_for_frames(&record, 10), 10);
+    let record = record.lock().unwrap();
+    assert!(record.frames.iter().all(|frame| frame.len() == LEDS * 4));
+
+    // The white effect only uses the white channel of RGBW leds
+    for frame in record.frames.iter() {
+        assert!(frame.chunks_exact(4).all(|pixel| pixel[..3] == [0, 0, 0]));
+    }
+    assert!(record.frames.iter().any(|frame| frame.chunks_exact(4).any(|pixel| pixel[3] > 0)));
+    Ok(())
+}
+
+/// Sink which panics at the first frame
+struct PanickingSink {
+    stops: Arc<Mutex<usize>>
+}
+
+impl OutputSink for PanickingSink {
+    fn write(&mut self, _frame: &[u8]) -> Result<()> {
+        panic!("The sink failed")
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        *self.stops.lock().unwrap() += 1;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_panicking_sink() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_millis(200)));
+
+    let stops = Arc::new(Mutex::new(0));
+    let mut engine = Engine::new(Box::new(generator), LEDS);
+    engine.set_sink(Box::new(PanickingSink { stops: stops.clone() }))?;
+    engine.set_effect(0)?;
+    sleep(Duration::from_millis(100));
+
+    // The panic of the audio thread doesn't reach the engine, which still stops the sink
+    drop(engine);
+    assert_eq!(*stops.lock().unwrap(), 1);
+    Ok(())
+}
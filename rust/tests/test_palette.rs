@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::Duration;
+use visualization_test::engine::Engine;
+use visualization_test::engine::effects::EffectProcessing;
+use visualization_test::engine::effects::frequency::FrequencyEffect;
+use visualization_test::engine::input::Playback;
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+use visualization_test::engine::output::CallbackSink;
+use visualization_test::engine::output::palette::{ColorMapping, ColorScheme, ColorStop, Gradient, NamedGradient, Palette};
+use visualization_test::engine::output::pixel::{to_byte, Color};
+
+use anyhow::Result;
+
+const LEDS: usize = 10;
+
+/// 8 bit channels of the colour
+fn bytes(color: Color) -> [u8; 3] {
+    [to_byte(color.red), to_byte(color.green), to_byte(color.blue)]
+}
+
+
+#[test]
+fn test_color_spaces() {
+    assert_eq!(bytes(Color::hsv(0.0, 1.0, 1.0)), [255, 0, 0]);
+    assert_eq!(bytes(Color::hsv(120.0, 1.0, 1.0)), [0, 255, 0]);
+    assert_eq!(bytes(Color::hsv(240.0, 1.0, 1.0)), [0, 0, 255]);
+    assert_eq!(bytes(Color::hsv(60.0, 1.0, 0.5)), [128, 128, 0]);
+    assert_eq!(bytes(Color::hsv(360.0, 0.0, 1.0)), [255, 255, 255]);
+
+    assert_eq!(bytes(Color::hsl(0.0, 1.0, 0.5)), [255, 0, 0]);
+    assert_eq!(bytes(Color::hsl(180.0, 1.0, 0.5)), [0, 255, 255]);
+    assert_eq!(bytes(Color::hsl(300.0, 1.0, 1.0)), [255, 255, 255]);
+    assert_eq!(bytes(Color::hsl(-120.0, 1.0, 0.25)), [0, 0, 128]);
+}
+
+#[test]
+fn test_gradient() -> Result<()> {
+    let gradient = Gradient::new(vec![
+        ColorStop::new(1.0, Color::rgb(0, 0, 255)),
+        ColorStop::new(0.5, Color::rgb(255, 0, 0)),
+        ColorStop::new(0.25, Color::rgb(255, 0, 0))
+    ])?;
+
+    // Stops are sorted and the colours outside of the stops are kept
+    assert_eq!(gradient.stops()[0].position, 0.25);
+    assert_eq!(bytes(gradient.color_at(0.0)), [255, 0, 0]);
+    assert_eq!(bytes(gradient.color_at(0.4)), [255, 0, 0]);
+    assert_eq!(bytes(gradient.color_at(0.75)), [128, 0, 128]);
+    assert_eq!(bytes(gradient.color_at(1.0)), [0, 0, 255]);
+
+    assert!(Gradient::new(vec![]).is_err());
+    assert!(Gradient::new(vec![ColorStop::new(1.5, Color::WHITE)]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_palettes() {
+    assert_eq!(NamedGradient::from_name("fire"), Some(NamedGradient::Fire));
+    assert_eq!(NamedGradient::from_name("unknown"), None);
+
+    // The named gradients don't differ from their gradient
+    for named in NamedGradient::ALL {
+        let gradient = named.gradient();
+        for position in [0.0, 0.1, 0.5, 0.77, 1.0] {
+            let expected = gradient.color_at(position).channels();
+            let color = Palette::Named(named).color_at(position).channels();
+            assert!(color.iter().zip(expected).all(|(a, b)| a.abs_diff(b) <= 1), "{}", named);
+        }
+    }
+
+    assert_eq!(bytes(Palette::RAINBOW.color_at(1.0 / 3.0)), [0, 255, 0]);
+    assert_eq!(bytes(Palette::RainbowHsl { saturation: 1.0, lightness: 0.5 }.color_at(2.0 / 3.0)), [0, 0, 255]);
+    assert_eq!(Palette::default().color_at(0.3), Color::WHITE);
+    assert_eq!(Palette::Named(NamedGradient::Fire).color_at(f32::NAN), Color::BLACK);
+}
+
+#[test]
+fn test_mapping() {
+    let values = [0, i16::MAX / 2, i16::MAX];
+    let mut pixels = [Color::BLACK; 3];
+
+    // The intensity selects the colour and the brightness
+    let scheme = ColorScheme::new(Palette::Named(NamedGradient::Heat), ColorMapping::Intensity);
+    scheme.apply(&values, &mut pixels);
+    assert_eq!(bytes(pixels[0]), [0, 0, 0]);
+    assert_eq!(bytes(pixels[1]), [64, 127, 0]);
+    assert_eq!(bytes(pixels[2]), [255, 0, 0]);
+
+    // The position selects the colour
+    let scheme = ColorScheme::new(Palette::RAINBOW, ColorMapping::Position);
+    scheme.apply(&[i16::MAX; 3], &mut pixels);
+    assert_eq!(bytes(pixels[0]), [255, 0, 0]);
+    assert_eq!(bytes(pixels[1]), [0, 255, 255]);
+    assert_eq!(bytes(pixels[2]), [255, 0, 0]);
+
+    // The default scheme keeps white leds
+    ColorScheme::default().apply(&values, &mut pixels);
+    assert_eq!(pixels[2], Color::WHITE);
+    assert_eq!(pixels[1], Color::WHITE.with_intensity(i16::MAX / 2));
+}
+
+#[test]
+fn test_mirrored_positions() {
+    let mut effect = FrequencyEffect;
+    let scheme = ColorScheme::new(Palette::Named(NamedGradient::Ocean), ColorMapping::Position);
+    let mut pixels = [Color::BLACK; LEDS];
+
+    effect.colorize(&[i16::MAX; LEDS], &scheme, &mut pixels);
+
+    // Both halves get the colours of their mel bin
+    for i in 0..LEDS / 2 {
+        assert_eq!(pixels[LEDS / 2 - 1 - i], pixels[LEDS / 2 + i]);
+    }
+    assert_eq!(pixels[LEDS / 2], NamedGradient::Ocean.color_at(0.0));
+    assert_eq!(pixels[LEDS - 1], NamedGradient::Ocean.color_at(1.0));
+}
+
+#[test]
+fn test_engine_scheme() -> Result<()> {
+    let mut generator = GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::Unpaced);
+    generator.set_duration(Some(Duration::from_millis(200)));
+
+    let frames = Arc::new(Mutex::new(vec![]));
+    let clone = frames.clone();
+
+    let mut engine = Engine::new(Box::new(generator), LEDS);
+    assert_eq!(engine.get_color_scheme(0)?, ColorScheme::default());
+    assert!(engine.get_color_scheme(100).is_err());
+    assert!(engine.set_color_scheme(100, ColorScheme::default()).is_err());
+
+    // Only the current effect restarts the stream
+    let red = ColorScheme::new(Palette::Solid(Color::rgb(255, 0, 0)), ColorMapping::Intensity);
+    engine.set_sink(Box::new(CallbackSink::new(move |frame: &[u8]| clone.lock().unwrap().push(frame.to_vec()))))?;
+    engine.set_color_scheme(1, red.clone())?;
+    assert_eq!(engine.get_color_scheme(1)?, red);
+    engine.set_color_scheme(0, red)?;
+
+    for _ in 0..500 {
+        if frames.lock().unwrap().len() >= 10 { break }
+        sleep(Duration::from_millis(10));
+    }
+    let frames = frames.lock().unwrap();
+    assert!(frames.len() >= 10);
+    assert!(frames.iter().all(|frame| frame.chunks_exact(3).all(|pixel| pixel[1..] == [0, 0])));
+    assert!(frames.iter().any(|frame| frame.chunks_exact(3).any(|pixel| pixel[0] > 0)));
+    Ok(())
+}
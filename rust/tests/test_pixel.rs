@@ -0,0 +1,64 @@
+use visualization_test::engine::output::pixel::{to_byte, ChannelOrder, Color, PixelFormat, PixelFrame};
+
+#[test]
+fn test_to_byte() {
+    assert_eq!(to_byte(0), 0);
+    assert_eq!(to_byte(u16::MAX), 255);
+    assert_eq!(to_byte(128 * 257), 128);
+
+    // Every 8 bit colour survives the conversion
+    for value in 0..=255u8 {
+        assert_eq!(to_byte(Color::rgb(value, 0, 0).red), value);
+    }
+}
+
+#[test]
+fn test_intensity() {
+    assert_eq!(Color::WHITE.with_intensity(i16::MAX), Color::WHITE);
+    assert_eq!(Color::WHITE.with_intensity(0), Color::BLACK);
+    assert_eq!(Color::WHITE.with_intensity(-100), Color::BLACK);
+
+    let half = Color::rgb(255, 128, 0).with_intensity(i16::MAX / 2);
+    assert_eq!(half.channels().map(to_byte), [127, 64, 0, 0]);
+}
+
+#[test]
+fn test_channel_order() {
+    let mut frame = PixelFrame::new(2);
+    frame.pixels_mut()[0] = Color::rgb(1, 2, 3);
+    frame.pixels_mut()[1] = Color::rgb(4, 5, 6);
+
+    let mut bytes = vec![];
+    frame.to_bytes(PixelFormat::RGB, &mut bytes);
+    assert_eq!(bytes, vec![1, 2, 3, 4, 5, 6]);
+
+    frame.to_bytes(PixelFormat::GRB, &mut bytes);
+    assert_eq!(bytes, vec![2, 1, 3, 5, 4, 6]);
+
+    frame.to_bytes(PixelFormat { order: ChannelOrder::Brg, white: false }, &mut bytes);
+    assert_eq!(bytes, vec![3, 1, 2, 6, 4, 5]);
+
+    // Every order contains the same channels
+    for order in ChannelOrder::ALL {
+        frame.to_bytes(PixelFormat { order, white: false }, &mut bytes);
+        let mut sorted = bytes[..3].to_vec();
+        sorted.sort();
+        assert_eq!(sorted, vec![1, 2, 3], "{}", order);
+    }
+}
+
+#[test]
+fn test_white_channel() {
+    let mut frame = PixelFrame::new(3);
+    frame.pixels_mut()[0] = Color::WHITE;
+    frame.pixels_mut()[1] = Color::rgb(200, 100, 50);
+    frame.pixels_mut()[2] = Color::rgb(0, 255, 0);
+
+    let mut bytes = vec![];
+    frame.to_bytes(PixelFormat::RGBW, &mut bytes);
+    assert_eq!(bytes, vec![0, 0, 0, 255, 150, 50, 0, 50, 0, 255, 0, 0]);
+
+    // The white channel is always sent last
+    frame.to_bytes(PixelFormat::GRBW, &mut bytes);
+    assert_eq!(bytes, vec![0, 0, 0, 255, 50, 150, 0, 50, 255, 0, 0, 0]);
+}
@@ -0,0 +1,177 @@
+use std::f32::consts::PI;
+use std::sync::Arc;
+use visualization_test::engine::input::InputConfig;
+use visualization_test::engine::processing::{interpolate, quantize, Spectrum, N_BINS, N_FFT};
+use visualization_test::engine::processing::window::Window;
+use visualization_test::engine::processing::mel::{hz_to_mel, mel_to_hz, MelCache, MelFilterbank};
+
+const SAMPLE_RATE: f32 = 48000.0;
+
+
+/// Full scale sine with the given length
+fn sine(frequency: f32, length: usize) -> Vec<i16> {
+    (0..length)
+        .map(|n| ((2.0 * PI * frequency * n as f32 / SAMPLE_RATE).sin() * i16::MAX as f32) as i16)
+        .collect()
+}
+
+/// Position of the largest value
+fn peak(values: &[f32]) -> usize {
+    values.iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, _)| i)
+        .unwrap()
+}
+
+#[test]
+fn test_sine_peak() {
+    let mut spectrum = Spectrum::new(Window::Hann, N_FFT);
+    let mut magnitude = [0.0; N_BINS];
+
+    // Frequency of the 40th bin
+    let frequency = 40.0 * SAMPLE_RATE / N_FFT as f32;
+    spectrum.magnitude(&sine(frequency, N_FFT), &mut magnitude);
+
+    assert_eq!(peak(&magnitude), 40);
+    // Full scale sine with hann window has a magnitude of N/4
+    assert!((magnitude[40] - N_FFT as f32 / 4.0).abs() < 1.0);
+}
+
+#[test]
+fn test_zero_padding() {
+    let mut spectrum = Spectrum::new(Window::Hann, N_FFT);
+    let mut power = [0.0; N_BINS];
+
+    // A shorter frame is padded, so the peak stays at the same frequency
+    spectrum.power(&sine(3000.0, 960), &mut power);
+    let expected = (3000.0 * N_FFT as f32 / SAMPLE_RATE).round() as usize;
+    assert!(peak(&power).abs_diff(expected) <= 1);
+
+    spectrum.power(&[0; 960], &mut power);
+    assert!(power.iter().all(|&value| value == 0.0));
+}
+
+#[test]
+fn test_long_frames() -> anyhow::Result<()> {
+    // At 30 fps a window contains more samples than the fourier transformation
+    let config = InputConfig { capture_frame_rate: 30, display_frame_rate: 30, ..Default::default() };
+    let length = config.buffer_info(SAMPLE_RATE as u32)?.window_length;
+    assert_eq!(length, 1600);
+
+    // Only the newest samples contain the sine, which still has to reach the spectrum
+    let mut frame = vec![0; length - 500];
+    frame.extend(sine(40.0 * SAMPLE_RATE / N_FFT as f32, 500));
+
+    let mut spectrum = Spectrum::new(Window::Hann, length);
+    let mut magnitude = [0.0; N_BINS];
+    spectrum.magnitude(&frame, &mut magnitude);
+    assert_eq!(peak(&magnitude), 40);
+    assert!(magnitude[40] > 1.0);
+    Ok(())
+}
+
+#[test]
+fn test_quantize() {
+    let mut spectrum = Spectrum::new(Window::Hann, N_FFT);
+    let mut power = [0.0; N_BINS];
+    let mut output = [0; N_BINS];
+
+    spectrum.power(&sine(40.0 * SAMPLE_RATE / N_FFT as f32, N_FFT), &mut power);
+    quantize(&power, spectrum.max_power(), &mut output);
+
+    assert!(output[40] > i16::MAX - 100);
+    assert!(output[200] < output[40] / 4);
+}
+
+#[test]
+fn test_interpolate() {
+    let mut output = [0.0; 5];
+    interpolate(&[0.0, 4.0, 8.0], &mut output);
+
+    assert_eq!(output, [0.0, 2.0, 4.0, 6.0, 8.0]);
+}
+
+#[test]
+fn test_mel_scale() {
+    assert!((hz_to_mel(1000.0) - 1000.0).abs() < 1.0);
+    assert!((mel_to_hz(hz_to_mel(440.0)) - 440.0).abs() < 0.01);
+}
+
+#[test]
+fn test_mel_peak() -> anyhow::Result<()> {
+    let filterbank = MelFilterbank::new(48000, 30, 20.0, 12000.0)?;
+    let mut spectrum = Spectrum::new(Window::Hann, N_FFT);
+    let mut power = [0.0; N_BINS];
+    let mut mel = vec![0.0; filterbank.n_mel()];
+
+    // Higher frequencies end up in higher mel bins
+    let mut last = 0;
+    for frequency in [200.0, 1000.0, 5000.0] {
+        spectrum.power(&sine(frequency, N_FFT), &mut power);
+        filterbank.apply(&power, &mut mel);
+
+        let position = peak(&mel);
+        assert!(position > last, "{} Hz peaks at mel bin {}", frequency, position);
+        last = position;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_mel_counts() -> anyhow::Result<()> {
+    // Flat spectrum: Every normalized filter returns the same value
+    let power = [1.0; N_BINS];
+
+    for n_mel in [1, 2, 300, 1000] {
+        let filterbank = MelFilterbank::new(44100, n_mel, 20.0, 22050.0)?;
+        let mut mel = vec![0.0; n_mel];
+        filterbank.apply(&power, &mut mel);
+
+        assert_eq!(filterbank.n_mel(), n_mel);
+        assert!(mel.iter().all(|&value| (value - 1.0).abs() < 0.001), "{} mel bins: {:?}", n_mel, mel);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_mel_cache() -> anyhow::Result<()> {
+    let mut cache = MelCache::new();
+
+    let first = cache.get(48000, 30, 20.0, 12000.0)?;
+    let second = cache.get(48000, 30, 20.0, 12000.0)?;
+    let other = cache.get(44100, 30, 20.0, 12000.0)?;
+
+    assert!(Arc::ptr_eq(&first, &second));
+    assert!(!Arc::ptr_eq(&first, &other));
+    assert!(cache.get(48000, 30, 12000.0, 20.0).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_windows() {
+    let mut magnitude = [0.0; N_BINS];
+
+    // Frequency between two bins leaks into the neighbours
+    let frame = sine(40.5 * SAMPLE_RATE / N_FFT as f32, N_FFT);
+    let mut leakage = vec![];
+
+    for window in Window::ALL {
+        let coefficients = window.coefficients(N_FFT);
+        assert_eq!(coefficients.len(), N_FFT);
+        assert!(coefficients.iter().all(|&value| (-0.01..=1.01).contains(&value)), "{}", window);
+
+        let mut spectrum = Spectrum::new(window, N_FFT);
+        spectrum.magnitude(&frame, &mut magnitude);
+        assert!(peak(&magnitude).abs_diff(40) <= 1, "{}", window);
+
+        // Leakage far away from the peak relative to the peak
+        leakage.push(magnitude[100] / magnitude[40]);
+    }
+
+    // The rectangular window leaks the most, the blackman harris the least
+    let rectangular = leakage[0];
+    let blackman_harris = leakage[4];
+    assert!(leakage.iter().all(|&value| value <= rectangular));
+    assert!(leakage.iter().all(|&value| value >= blackman_harris));
+}
@@ -0,0 +1,208 @@
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+use cpal::StreamError;
+use visualization_test::engine::Engine;
+use visualization_test::engine::errors::ApplicationError;
+use visualization_test::engine::events::EngineEvent;
+use visualization_test::engine::input::{DataCallback, DeviceInfo, ErrorCallback, InputConfig, InputSource, Playback};
+use visualization_test::engine::input::generator::{GeneratorInputSource, Signal};
+
+use anyhow::Result;
+
+const LEDS: usize = 60;
+
+
+/// Devices of the *FlakyInputSource*, which can be plugged and unplugged by the test
+#[derive(Default)]
+struct Devices {
+    // Name and standard flag of the plugged devices
+    plugged: Vec<(String, bool)>,
+    error_callback: Option<ErrorCallback>
+}
+
+impl Devices {
+    fn plug(&mut self, name: &str, standard: bool) {
+        self.plugged.push((name.to_string(), standard));
+    }
+
+    /// Remove the device and report the error to the running stream
+    fn unplug(&mut self, name: &str) {
+        self.plugged.retain(|(device, _)| device != name);
+        if let Some(callback) = &mut self.error_callback {
+            callback(StreamError::DeviceNotAvailable);
+        }
+    }
+}
+
+/// Input source with devices, which can disappear while the stream is running
+struct FlakyInputSource {
+    devices: Arc<Mutex<Devices>>,
+    device: Option<String>,
+    inner: GeneratorInputSource
+}
+
+impl FlakyInputSource {
+    fn new() -> (Self, Arc<Mutex<Devices>>) {
+        let devices = Arc::new(Mutex::new(Devices::default()));
+        let source = FlakyInputSource {
+            devices: devices.clone(),
+            device: None,
+            inner: GeneratorInputSource::new(Signal::WhiteNoise, 48000, Playback::RealTime)
+        };
+        (source, devices)
+    }
+}
+
+impl InputSource for FlakyInputSource {
+    fn available_devices(&self) -> Result<Vec<DeviceInfo>> {
+        let devices = self.devices.lock().unwrap();
+        Ok(devices.plugged.iter()
+            .enumerate()
+            .map(|(position, (name, standard))| DeviceInfo {
+                position,
+                name: name.clone(),
+                channels: 1,
+                sample_rate: 48000,
+                standard: *standard
+            })
+            .collect())
+    }
+
+    fn set_device(&mut self, position: usize) -> Result<()> {
+        let devices = self.devices.lock().unwrap();
+        let (name, _) = devices.plugged.get(position)
+            .ok_or_else(|| ApplicationError::DeviceNotFound(position.to_string()))?;
+        self.device = Some(name.clone());
+        Ok(())
+    }
+
+    /// Like a real device, an unplugged device doesn't know its name anymore
+    fn current_device_name(&self) -> Result<String> {
+        let name = self.device.clone().ok_or(ApplicationError::NoDeviceSelected)?;
+        match self.devices.lock().unwrap().plugged.iter().any(|(device, _)| *device == name) {
+            true => Ok(name),
+            false => Ok(String::from("<Unknown>"))
+        }
+    }
+
+    fn sample_rate(&self) -> Result<u32> {
+        self.inner.sample_rate()
+    }
+
+    fn build_stream(&mut self, config: &InputConfig, callback: DataCallback, error_callback: ErrorCallback) -> Result<()> {
+        let name = self.current_device_name()?;
+        let mut devices = self.devices.lock().unwrap();
+        if !devices.plugged.iter().any(|(device, _)| *device == name) {
+            Err(ApplicationError::DeviceNotFound(name))?
+        }
+
+        devices.error_callback = Some(error_callback);
+        self.inner.build_stream(config, callback, Box::new(|_| {}))
+    }
+
+    fn start_stream(&self) -> Result<()> {
+        self.inner.start_stream()
+    }
+
+    fn pause_stream(&self) -> Result<()> {
+        self.inner.pause_stream()
+    }
+}
+
+
+/// Wait until the error was reported and handle it
+fn maintain(engine: &mut Engine) -> Result<()> {
+    std::thread::sleep(Duration::from_millis(10));
+    engine.maintain_stream()
+}
+
+fn received(events: &Receiver<EngineEvent>) -> Vec<EngineEvent> {
+    events.try_iter().collect()
+}
+
+#[test]
+fn test_fallback_to_default() -> Result<()> {
+    let (input, devices) = FlakyInputSource::new();
+    devices.lock().unwrap().plug("Default", true);
+    devices.lock().unwrap().plug("Interface", false);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    engine.set_retry_interval(Duration::ZERO);
+    let events = engine.subscribe();
+    engine.set_device_by_name("Interface")?;
+
+    // Nothing happens while the stream runs
+    maintain(&mut engine)?;
+    assert!(!engine.is_recovering());
+    assert!(received(&events).is_empty());
+
+    devices.lock().unwrap().unplug("Interface");
+    maintain(&mut engine)?;
+    assert!(engine.is_recovering());
+    assert_eq!(engine.get_current_device_name()?, "Default");
+    assert_eq!(received(&events), vec![
+        EngineEvent::StreamError(StreamError::DeviceNotAvailable.to_string()),
+        EngineEvent::FallbackDevice(String::from("Default"))
+    ]);
+
+    // The device isn't available yet
+    maintain(&mut engine)?;
+    assert!(engine.is_recovering());
+
+    devices.lock().unwrap().plug("Interface", false);
+    maintain(&mut engine)?;
+    assert!(!engine.is_recovering());
+    assert_eq!(engine.get_current_device_name()?, "Interface");
+    assert_eq!(received(&events), vec![EngineEvent::DeviceRecovered(String::from("Interface"))]);
+    Ok(())
+}
+
+#[test]
+fn test_fallback_to_silence() -> Result<()> {
+    let (input, devices) = FlakyInputSource::new();
+    devices.lock().unwrap().plug("Interface", true);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    engine.set_retry_interval(Duration::ZERO);
+    let events = engine.subscribe();
+    engine.set_device(0)?;
+
+    devices.lock().unwrap().unplug("Interface");
+    maintain(&mut engine)?;
+    assert!(engine.is_recovering());
+    assert_eq!(engine.get_current_device_name()?, "Generator: Silence");
+    assert!(received(&events).contains(&EngineEvent::FallbackSilence));
+
+    // The devices of the original input are still listed
+    assert!(engine.get_available_devices()?.is_empty());
+
+    devices.lock().unwrap().plug("Interface", true);
+    maintain(&mut engine)?;
+    assert!(!engine.is_recovering());
+    assert_eq!(engine.get_current_device_name()?, "Interface");
+    Ok(())
+}
+
+#[test]
+fn test_retry_interval() -> Result<()> {
+    let (input, devices) = FlakyInputSource::new();
+    devices.lock().unwrap().plug("Interface", true);
+
+    let mut engine = Engine::new(Box::new(input), LEDS);
+    engine.set_device(0)?;
+
+    devices.lock().unwrap().unplug("Interface");
+    maintain(&mut engine)?;
+    devices.lock().unwrap().plug("Interface", true);
+
+    // The next retry isn't due yet
+    maintain(&mut engine)?;
+    assert!(engine.is_recovering());
+
+    // Selecting a device stops the recovery
+    engine.set_device(0)?;
+    assert!(!engine.is_recovering());
+    assert_eq!(engine.get_current_device_name()?, "Interface");
+    Ok(())
+}
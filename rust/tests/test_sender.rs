@@ -3,7 +3,7 @@ extern crate core;
 use std::thread::sleep;
 use std::time::Duration;
 use visualization_test::engine::errors::SenderError;
-use visualization_test::engine::sender::Sender;
+use visualization_test::engine::sender::{Sender, UniverseRange};
 
 use anyhow::Result;
 
@@ -13,6 +13,168 @@ fn test_creation() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_frame_rate() -> Result<()> {
+    let sender = Sender::new()?;
+    assert_eq!(sender.frame_rate(), None);
+
+    let error = sender.set_frame_rate(0).unwrap_err();
+    assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::InvalidFrameRate(0))));
+
+    // The frame rate is shared with all clones
+    sender.set_frame_rate(30)?;
+    let clone = sender.clone()?;
+    assert_eq!(clone.frame_rate(), Some(30));
+
+    // Packets faster than the frame rate are dropped without an error
+    for _ in 0..10 {
+        sender.send(&[255; 3])?;
+        clone.send(&[255; 3])?;
+    }
+    Ok(())
+}
+
+#[test]
+fn test_blackout() -> Result<()> {
+    let sender = Sender::new()?;
+    let clone = sender.clone()?;
+    sender.set_frame_rate(1)?;
+
+    sender.send(&[255; 3])?;
+    clone.send(&[255; 3])?;
+    assert_eq!(sender.sent_packets(), 1);
+
+    // The next packet isn't due yet and the clone hasn't written its data
+    sender.send(&[255; 3])?;
+    assert_eq!(sender.sent_packets(), 1);
+
+    // The blackout is sent anyway
+    sender.blackout()?;
+    assert_eq!(sender.sent_packets(), 2);
+    Ok(())
+}
+
+#[test]
+fn test_universe_spanning() -> Result<()> {
+    let sender = Sender::new()?;
+    assert_eq!(sender.universes(), vec![1]);
+    assert_eq!(sender.capacity(), 512);
+
+    // 600 rgb pixels need 4 universes with 510 channels
+    sender.set_channels_per_universe(510)?;
+    sender.send(&[255; 600*3])?;
+    assert_eq!(sender.universes(), vec![1, 2, 3, 4]);
+    assert_eq!(sender.capacity(), 4*510);
+
+    // The clone uses the universes behind the first sender
+    let clone = sender.clone()?;
+    assert_eq!(clone.channels_per_universe(), 510);
+    assert_eq!(clone.universes(), vec![5]);
+    clone.send(&[255; 200*3])?;
+    sender.send(&[255; 600*3])?;
+    assert_eq!(clone.universes(), vec![5, 6]);
+
+    // Shorter data gives the unused universes back, but the clone keeps its universes
+    sender.send(&[0; 10])?;
+    clone.send(&[0; 10])?;
+    assert_eq!(sender.universes(), vec![1]);
+    assert_eq!(clone.universes(), vec![5]);
+
+    for channels in [0, 513] {
+        let error = sender.set_channels_per_universe(channels).unwrap_err();
+        assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::InvalidChannelsPerUniverse(_))));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_universe_shrinking() -> Result<()> {
+    let sender = Sender::new()?;
+    sender.set_channels_per_universe(510)?;
+    sender.send(&[255; 600*3])?;
+    assert_eq!(sender.universes(), vec![1, 2, 3, 4]);
+
+    // Shorter data gives the unused universes back, the blackout only covers the remaining ones
+    sender.send(&[255; 100*3])?;
+    assert_eq!(sender.universes(), vec![1]);
+    assert_eq!(sender.capacity(), 510);
+    sender.blackout()?;
+
+    // Fixed ranges keep their universes
+    let fixed = sender.clone_with_universes(UniverseRange::new(10, 2))?;
+    fixed.send(&[255; 3])?;
+    assert_eq!(fixed.universes(), vec![10, 11]);
+    Ok(())
+}
+
+#[test]
+fn test_universe_assignment() -> Result<()> {
+    let sender = Sender::with_universes(UniverseRange::new(10, 2))?;
+    assert_eq!(sender.universes(), vec![10, 11]);
+
+    // Explicit ranges don't overlap
+    let clone = sender.clone_with_universes(UniverseRange::new(20, 3))?;
+    assert_eq!(clone.universes(), vec![20, 21, 22]);
+    for range in [UniverseRange::new(11, 1), UniverseRange::new(5, 6), UniverseRange::new(22, 10)] {
+        let error = sender.clone_with_universes(range).err().unwrap();
+        assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::UniverseOverlap(_))), "{:?}", range);
+    }
+
+    // Invalid sacn universes
+    for range in [UniverseRange::new(0, 1), UniverseRange::new(1, 0), UniverseRange::new(63999, 2)] {
+        let error = Sender::with_universes(range).err().unwrap();
+        assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::InvalidUniverses(_))), "{:?}", range);
+    }
+
+    // The data has to fit into the fixed universes
+    sender.set_channels_per_universe(510)?;
+    clone.send(&[255; 3*510])?;
+    sender.send(&[255; 2*510])?;
+    let error = sender.send(&[255; 2*510 + 1]).unwrap_err();
+    assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::WrongPacketSize)));
+
+    // An automatic clone begins behind all other universes
+    let automatic = sender.clone()?;
+    assert_eq!(automatic.universes(), vec![23]);
+    assert!(sender.clone().is_err());
+    Ok(())
+}
+
+#[test]
+fn test_growth_overlap() -> Result<()> {
+    let sender = Sender::new()?;
+    let clone = sender.clone_with_universes(UniverseRange::new(3, 1))?;
+
+    // The automatic sender can only grow until the next range
+    sender.send(&[255; 2*512])?;
+    clone.send(&[0; 3])?;
+    assert_eq!(sender.universes(), vec![1, 2]);
+
+    let error = sender.send(&[255; 3*512]).unwrap_err();
+    assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::WrongPacketSize)));
+    assert_eq!(sender.universes(), vec![1, 2]);
+
+    // A fixed range can't take the universes of an automatic sender
+    let error = sender.clone_with_universes(UniverseRange::new(2, 1)).err().unwrap();
+    assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::UniverseOverlap(_))));
+    Ok(())
+}
+
+#[test]
+fn test_clone_before_growth() -> Result<()> {
+    let sender = Sender::new()?;
+    let clone = sender.clone()?;
+    assert_eq!(clone.universes(), vec![2]);
+
+    // The clone keeps its universe, so the first sender can't grow into it
+    sender.set_channels_per_universe(510)?;
+    let error = sender.send(&[255; 600*3]).unwrap_err();
+    assert!(matches!(error.downcast_ref::<SenderError>(), Some(SenderError::WrongPacketSize)));
+    assert_eq!(sender.universes(), vec![1]);
+    assert_eq!(clone.universes(), vec![2]);
+    Ok(())
+}
+
 #[test]
 fn test_sync() -> Result<()> {
     let white: Vec<u8> = vec![255; 60*3];